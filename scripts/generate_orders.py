@@ -0,0 +1,52 @@
+#!/usr/bin/env python3
+"""Write an orders-like csv of roughly the given size for throughput tests.
+
+About 1% of the cells are dirty (NULL, N/A, empty, whitespace, none).
+
+    python3 scripts/generate_orders.py 2 orders_2gb.csv   # ~2 GiB, ~25M rows
+"""
+import random
+import sys
+
+target = float(sys.argv[1]) * 1024**3
+rnd = random.Random(42)
+skus = ["SKU%03d" % i for i in range(500)]
+nulls = ["NULL", "N/A", "", "   ", "none"]
+
+
+def dirty(value):
+    return rnd.choice(nulls) if rnd.random() < 0.01 else value
+
+
+with open(sys.argv[2], "w", buffering=1 << 22) as out:
+    out.write(
+        "customer_id,order_date,product_sku,quantity,unit_price,"
+        "shipping_zip,email,last_updated_timestamp\n"
+    )
+    size = 0
+    i = 0
+    while size < target:
+        rows = []
+        for _ in range(10000):
+            i += 1
+            m, d = rnd.randint(1, 12), rnd.randint(1, 28)
+            rows.append(
+                ",".join(
+                    [
+                        dirty(str(100000 + i)),
+                        dirty("%d/%d/2024" % (m, d)),
+                        dirty(rnd.choice(skus)),
+                        dirty(str(rnd.randint(1, 20))),
+                        dirty("%.2f" % (rnd.random() * 200)),
+                        dirty("%05d" % rnd.randint(501, 99950)),
+                        dirty("user%d@example.com" % i),
+                        dirty(
+                            "2024-%02d-%02dT%02d:%02d:00Z"
+                            % (m, d, rnd.randint(0, 23), rnd.randint(0, 59))
+                        ),
+                    ]
+                )
+            )
+        chunk = "\n".join(rows) + "\n"
+        out.write(chunk)
+        size += len(chunk)
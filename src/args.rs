@@ -1,13 +1,113 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use encoding_rs::Encoding;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use true_sight_csv::config::CONFIG_FILE_NAME;
+use true_sight_csv::threshold::{resolve_check_name, ColumnLimit, ThresholdRule};
+use true_sight_csv::{
+    expand_inputs, parse_encoding_label, ColumnRules, ConfigFile, DateWindow, Dialect,
+    DuplicateConfig, DuplicateDetector, EmptyCheck, FindingsConfig, FindingsFormat, FixOptions,
+    InputSource, LineTerminator, NullConfig, NullLikeCheck, QuarantineConfig, WhiteSpaceOnlyCheck,
+};
 
-#[derive(Debug, Parser)]
-#[clap(author, version, about)]
-pub struct TrueSightCsvArgs {
-    /// The full path to the csv file to be inspected.
-    pub file_full_path: PathBuf,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Spark-style ASCII tables.
+    Table,
+    /// A single JSON document on stdout, progress messages go to stderr.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FindingsFileFormat {
+    /// One finding per CSV row.
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LineEnding {
+    /// Unix `\n`.
+    Lf,
+    /// Windows `\r\n`.
+    Crlf,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write a cleaned copy of a csv file: trimmed cells, one canonical null, one line ending.
+    Fix(FixArgs),
+}
+
+#[derive(Debug, Clone, Default, Args)]
+pub struct NullArgs {
+    /// Values treated as NULL, comma separated, e.g. `\N,null,(null),-,#N/A`. Replaces the built in list.
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub null_values: Option<Vec<String>>,
+
+    /// NULL values for one column, e.g. `ship_date=1900-01-01,0000-00-00`. Can be repeated.
+    #[arg(long, value_name = "COLUMN=LIST")]
+    pub null_column: Vec<String>,
+
+    /// Match NULL values case-sensitively.
+    #[arg(long)]
+    pub null_case_sensitive: bool,
+}
+
+impl NullArgs {
+    /// Put the NULL-like options from the command line on top of the config file
+    pub fn apply(&self, config: &mut NullConfig) -> Result<(), Box<dyn Error>> {
+        let mut columns = BTreeMap::new();
+        for entry in &self.null_column {
+            let (column, values) = entry.split_once('=').ok_or_else(|| {
+                format!("Expected COLUMN=LIST for --null-column, got '{}'", entry)
+            })?;
+            columns.insert(
+                column.trim().to_string(),
+                values.split(',').map(|v| v.trim().to_string()).collect(),
+            );
+        }
+
+        config.merge(NullConfig {
+            values: self.null_values.clone(),
+            case_sensitive: self.null_case_sensitive,
+            columns,
+        });
+        Ok(())
+    }
+}
+
+/// Load the config file given with --config, or the truesight.toml next to the first
+/// input when there is one, and apply the NULL-like options from the command line
+pub fn load_config(
+    path: Option<&PathBuf>,
+    first_source: &InputSource,
+    nulls: &NullArgs,
+) -> Result<(Option<PathBuf>, ConfigFile), Box<dyn Error>> {
+    let path = path.cloned().or_else(|| match first_source {
+        InputSource::File(file) => {
+            let candidate = file
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(CONFIG_FILE_NAME);
+            candidate.is_file().then_some(candidate)
+        }
+        InputSource::Stdin => None,
+    });
+
+    let mut config = match &path {
+        Some(path) => ConfigFile::load(path)?,
+        None => ConfigFile::default(),
+    };
+    nulls.apply(&mut config.nulls)?;
+    Ok((path, config))
+}
 
+// Chunking options shared by the check run and `fix`
+#[derive(Debug, Clone, Args)]
+pub struct ChunkArgs {
     /// The number of rows to use in a chunk. Default is 1_000_000.
     #[arg(long, default_value = "1000000")]
     pub row_chunk_size: usize,
@@ -17,32 +117,343 @@ pub struct TrueSightCsvArgs {
     pub disable_parallel: bool,
 }
 
+impl ChunkArgs {
+    /// Returns true if parallel execution is enabled (default behavior)
+    pub fn is_parallel_enabled(&self) -> bool {
+        !self.disable_parallel
+    }
+}
+
+// How the input is decoded and split into fields, shared by the check run and `fix`
+#[derive(Debug, Clone, Default, Args)]
+pub struct DialectArgs {
+    /// Field delimiter, e.g. `;`, `|` or `\t`. Detected from the start of the file when not given.
+    #[arg(long, value_parser = parse_dialect_byte)]
+    pub delimiter: Option<u8>,
+
+    /// Quote character. Detected from the start of the file when not given.
+    #[arg(long, value_parser = parse_dialect_byte)]
+    pub quote: Option<u8>,
+
+    /// Character encoding, e.g. `utf-8`, `latin1`, `windows-1252`, `utf-16le`. Detected from
+    /// the BOM and the start of the file when not given.
+    #[arg(long, value_parser = parse_encoding_label)]
+    pub encoding: Option<&'static Encoding>,
+
+    /// Treat the first row as data, columns are named col_0, col_1, ...
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Treat the first row as the header, even when it looks like data.
+    #[arg(long, conflicts_with = "no_header")]
+    pub header: bool,
+}
+
+impl DialectArgs {
+    /// Apply the explicit dialect options on top of what was sniffed
+    pub fn apply_dialect_overrides(&self, detected: Dialect) -> Dialect {
+        Dialect {
+            delimiter: self.delimiter.unwrap_or(detected.delimiter),
+            quote: self.quote.unwrap_or(detected.quote),
+            has_headers: (detected.has_headers || self.header) && !self.no_header,
+            ..detected
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct FixArgs {
+    /// Csv file to clean (optionally compressed), `-` reads from stdin.
+    pub input: PathBuf,
+
+    /// Where to write the cleaned csv, `-` for stdout.
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Value written for NULL-like and whitespace-only cells: `empty` or `\N`.
+    #[arg(long, default_value = "empty", value_parser = parse_null_value)]
+    pub null_as: String,
+
+    /// Line ending of the cleaned file.
+    #[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+    pub line_ending: LineEnding,
+
+    /// Keep a trailing column without a name instead of dropping it.
+    #[arg(long)]
+    pub keep_trailing_column: bool,
+
+    #[command(flatten)]
+    pub chunks: ChunkArgs,
+
+    #[command(flatten)]
+    pub dialect: DialectArgs,
+
+    /// Config file (truesight.toml) with a `[nulls]` table, found next to the input when not given.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub nulls: NullArgs,
+}
+
+#[derive(Debug, Parser)]
+#[clap(author, version, about, subcommand_negates_reqs = true)]
+pub struct TrueSightCsvArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Csv files, glob patterns or directories to inspect, `-` reads from stdin.
+    /// Files may be gzip/zstd/bzip2/xz compressed.
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub chunks: ChunkArgs,
+
+    /// Memory-map the file and parse byte ranges of it on separate threads. Only for
+    /// uncompressed files on disk.
+    #[arg(long)]
+    pub mmap: bool,
+
+    /// Read raw bytes instead of UTF-8 text. Cells that aren't valid UTF-8 are reported per
+    /// column with their line numbers instead of stopping the run. The file is read as UTF-8
+    /// unless `--encoding` is given.
+    #[arg(long)]
+    pub byte_records: bool,
+
+    /// Chunks that may be read ahead of the checks, bounds memory to about this many chunks.
+    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_in_flight_chunks: u64,
+
+    #[command(flatten)]
+    pub dialect: DialectArgs,
+
+    /// Keep going on rows with too few or too many fields and report them as ragged rows.
+    #[arg(long)]
+    pub tolerant: bool,
+
+    /// Config file (truesight.toml) with `[nulls]` and `[columns.*]` rules, found next to the input when not given.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub nulls: NullArgs,
+
+    /// Values of a column must match a regex or a preset (@email, @zip, @uuid, @iso8601, @phone, @sku), e.g. `email=@email`. Can be repeated.
+    #[arg(long, value_name = "COLUMN=PATTERN")]
+    pub regex: Vec<String>,
+
+    /// Rule over several fields of a row, e.g. `'quantity * unit_price >= 0'` or `'if empty(email) then present(phone)'`. Can be repeated.
+    #[arg(long, value_name = "EXPR")]
+    pub row_rule: Vec<String>,
+
+    /// Dates outside this window are reported as implausible, e.g. `2000-01-01..2030-12-31`. Either end can be left out.
+    #[arg(long, value_name = "START..END")]
+    pub date_window: Option<DateWindow>,
+
+    /// Write every flagged cell (file, line, byte offset, column, check, value) to this file.
+    #[arg(long, value_name = "PATH")]
+    pub findings_out: Option<PathBuf>,
+
+    /// Format of the findings file, taken from its extension when not given (.ndjson/.jsonl or csv).
+    #[arg(long, value_enum)]
+    pub findings_format: Option<FindingsFileFormat>,
+
+    /// Max findings written per check and column, 0 for no limit.
+    #[arg(long, default_value = "1000")]
+    pub findings_cap: usize,
+
+    /// Split the rows into clean.csv and rejected.csv in this directory, keeping their original quoting.
+    #[arg(long, value_name = "DIR")]
+    pub quarantine_dir: Option<PathBuf>,
+
+    /// Check that sends a row to rejected.csv, e.g. `null`, `empty`, `whitespace`. Can be repeated, default is every check.
+    #[arg(long, value_name = "CHECK", requires = "quarantine_dir")]
+    pub reject_on: Vec<String>,
+
+    /// Output format for the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+
+    /// Max NULL-like percentage for a column, e.g. `email=5` or `*=10`. Can be repeated.
+    #[arg(long, value_name = "COLUMN=PCT")]
+    pub max_null_pct: Vec<ColumnLimit>,
+
+    /// Max empty percentage for a column, e.g. `email=5` or `*=10`. Can be repeated.
+    #[arg(long, value_name = "COLUMN=PCT")]
+    pub max_empty_pct: Vec<ColumnLimit>,
+
+    /// Max whitespace-only percentage for a column, e.g. `email=5` or `*=10`. Can be repeated.
+    #[arg(long, value_name = "COLUMN=PCT")]
+    pub max_whitespace_pct: Vec<ColumnLimit>,
+
+    /// Fail if a check finds anything at all: null, empty, whitespace or a check name. Can be repeated.
+    #[arg(long, value_name = "CHECK")]
+    pub fail_on: Vec<String>,
+
+    /// Report rows that are exact duplicates of an earlier row.
+    #[arg(long)]
+    pub duplicates: bool,
+
+    /// Report duplicate composite keys, e.g. `customer_id,order_date`. Can be repeated.
+    #[arg(long, value_name = "COLUMNS")]
+    pub key: Vec<String>,
+
+    /// Spill duplicate fingerprints to this directory instead of keeping them in memory.
+    #[arg(long, value_name = "DIR")]
+    pub duplicate_spill_dir: Option<PathBuf>,
+
+    /// The number of line numbers to report per duplicate group.
+    #[arg(long, default_value = "5")]
+    pub max_duplicate_lines: usize,
+}
+
+// A single ASCII character, with `\t` / `tab` accepted for tabs
+fn parse_dialect_byte(value: &str) -> Result<u8, String> {
+    match value {
+        "\\t" | "\t" | "tab" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "expected a single ASCII character, got '{}'",
+            value
+        )),
+    }
+}
+
+// `empty` (or nothing) for an empty cell, anything else is written as is, e.g. `\N`
+fn parse_null_value(value: &str) -> Result<String, String> {
+    match value {
+        "" | "empty" => Ok(String::new()),
+        _ => Ok(value.to_string()),
+    }
+}
+
+impl FixArgs {
+    pub fn fix_options(&self, null_check: NullLikeCheck) -> FixOptions {
+        FixOptions {
+            null_check,
+            null_value: self.null_as.clone(),
+            line_terminator: match self.line_ending {
+                LineEnding::Lf => LineTerminator::Lf,
+                LineEnding::Crlf => LineTerminator::CrLf,
+            },
+            drop_trailing_column: !self.keep_trailing_column,
+        }
+    }
+}
+
 impl TrueSightCsvArgs {
-    pub fn validate_csv_path(&self) -> Result<&PathBuf, Box<dyn Error>> {
-        // Check existence first
-        if !self.file_full_path.exists() {
-            return Err(format!("File does not exist: {}", self.file_full_path.display()).into());
+    /// Expand the inputs into files (and stdin), each one validated
+    pub fn resolve_inputs(&self) -> Result<Vec<InputSource>, Box<dyn Error>> {
+        expand_inputs(&self.inputs)
+    }
+
+    /// Where and how to write row-level findings, if requested
+    pub fn findings_config(&self) -> Option<(&PathBuf, FindingsConfig)> {
+        let path = self.findings_out.as_ref()?;
+        let format = match self.findings_format {
+            Some(FindingsFileFormat::Csv) => FindingsFormat::Csv,
+            Some(FindingsFileFormat::Ndjson) => FindingsFormat::Ndjson,
+            None => FindingsFormat::from_path(path),
+        };
+        Some((
+            path,
+            FindingsConfig {
+                format,
+                max_per_check_column: self.findings_cap,
+            },
+        ))
+    }
+
+    /// Quarantine settings, if a quarantine directory was given
+    pub fn quarantine_config(&self) -> Option<QuarantineConfig> {
+        let dir = self.quarantine_dir.clone()?;
+        Some(QuarantineConfig {
+            dir,
+            reject_on: self
+                .reject_on
+                .iter()
+                .map(|check| resolve_check_name(check).to_string())
+                .collect(),
+        })
+    }
+
+    /// Add the `--regex` patterns to the column rules, replacing a regex from the config file
+    pub fn apply_regex_rules(
+        &self,
+        columns: &mut BTreeMap<String, ColumnRules>,
+    ) -> Result<(), Box<dyn Error>> {
+        for entry in &self.regex {
+            let (column, pattern) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected COLUMN=PATTERN for --regex, got '{}'", entry))?;
+            columns.entry(column.trim().to_string()).or_default().regex = Some(pattern.to_string());
         }
+        Ok(())
+    }
 
-        // Check if it's actually a file (not a directory)
-        if !self.file_full_path.is_file() {
-            return Err(format!(
-                "Path exists but is not a file: {}",
-                self.file_full_path.display()
-            )
-            .into());
+    /// Add the `--row-rule` expressions to the row rules of the config file
+    pub fn apply_row_rules(&self, rows: &mut BTreeMap<String, String>) {
+        for rule in &self.row_rule {
+            rows.insert(rule.clone(), rule.clone());
         }
+    }
+
+    /// All threshold options combined into rules keyed by check name
+    pub fn threshold_rules(&self) -> Vec<ThresholdRule> {
+        let limits = [
+            (NullLikeCheck::NAME, &self.max_null_pct),
+            (EmptyCheck::NAME, &self.max_empty_pct),
+            (WhiteSpaceOnlyCheck::NAME, &self.max_whitespace_pct),
+        ];
+
+        let mut rules: Vec<ThresholdRule> = limits
+            .iter()
+            .flat_map(|(check_name, limits)| {
+                limits
+                    .iter()
+                    .map(|limit| ThresholdRule::new(check_name, limit.clone()))
+            })
+            .collect();
 
-        // Check file extension
-        match self.file_full_path.extension() {
-            Some(ext) if ext == "csv" => Ok(&self.file_full_path),
-            _ => Err(format!("File must be a csv: {}", self.file_full_path.display()).into()),
+        rules.extend(
+            self.fail_on
+                .iter()
+                .map(|check| ThresholdRule::fail_on(resolve_check_name(check))),
+        );
+
+        rules
+    }
+
+    /// Settings shared by every duplicate detector
+    pub fn duplicate_base(&self) -> DuplicateConfig {
+        DuplicateConfig {
+            max_lines_per_group: self.max_duplicate_lines,
+            spill_dir: self.duplicate_spill_dir.clone(),
+            ..Default::default()
         }
     }
 
-    /// Returns true if parallel execution is enabled (default behavior)
-    pub fn is_parallel_enabled(&self) -> bool {
-        !self.disable_parallel
+    /// One duplicate detector config for `--duplicates` and for every `--key`
+    pub fn duplicate_configs(
+        &self,
+        headers: &[String],
+    ) -> Result<Vec<DuplicateConfig>, Box<dyn Error>> {
+        let base = self.duplicate_base();
+
+        let mut configs = Vec::new();
+        if self.duplicates {
+            configs.push(base.clone());
+        }
+        for key in &self.key {
+            let names: Vec<String> = key.split(',').map(|name| name.trim().to_string()).collect();
+            configs.push(DuplicateConfig {
+                key_columns: Some(DuplicateDetector::resolve_key_columns(&names, headers)?),
+                ..base.clone()
+            });
+        }
+
+        Ok(configs)
     }
 }
 #[cfg(test)]
@@ -54,19 +465,344 @@ mod tests {
     fn test_is_parallel_enabled() {
         // Default should be parallel enabled
         let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
-        assert!(args.is_parallel_enabled());
+        assert!(args.chunks.is_parallel_enabled());
 
         // With --disable-parallel flag, should be disabled
         let args =
             TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--disable-parallel"]).unwrap();
-        assert!(!args.is_parallel_enabled());
+        assert!(!args.chunks.is_parallel_enabled());
+    }
+
+    #[test]
+    fn test_encoding_option() {
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
+        assert!(args.dialect.encoding.is_none());
+
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--encoding", "latin1"]).unwrap();
+        assert_eq!(args.dialect.encoding, Some(encoding_rs::WINDOWS_1252));
+
+        assert!(
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--encoding", "ebcdic"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_max_in_flight_chunks() {
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
+        assert_eq!(args.max_in_flight_chunks, 2);
+
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--max-in-flight-chunks", "8"])
+                .unwrap();
+        assert_eq!(args.max_in_flight_chunks, 8);
+
+        assert!(TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--max-in-flight-chunks",
+            "0"
+        ])
+        .is_err());
     }
 
     #[test]
     fn test_default_values() {
         let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
-        assert_eq!(args.row_chunk_size, 1_000_000);
-        assert!(!args.disable_parallel); // Default is parallel enabled
+        assert_eq!(args.chunks.row_chunk_size, 1_000_000);
+        assert!(!args.chunks.disable_parallel); // Default is parallel enabled
+        assert_eq!(args.format, OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_threshold_rules() {
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
+        assert!(args.threshold_rules().is_empty());
+
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--max-null-pct",
+            "email=5",
+            "--max-empty-pct",
+            "*=10",
+            "--fail-on",
+            "whitespace",
+        ])
+        .unwrap();
+        let rules = args.threshold_rules();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].check_name, NullLikeCheck::NAME);
+        assert_eq!(rules[1].check_name, EmptyCheck::NAME);
+        assert_eq!(rules[2].check_name, WhiteSpaceOnlyCheck::NAME);
+        assert_eq!(rules[2].limit.max_percent, 0.0);
+
+        // Bad limits are rejected while parsing
+        assert!(
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--max-null-pct", "email"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_json_format() {
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--format", "json"]).unwrap();
+        assert_eq!(args.format, OutputFormat::Json);
+
+        assert!(TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_configs() {
+        let headers = vec!["customer_id".to_string(), "order_date".to_string()];
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
+        assert!(args.duplicate_configs(&headers).unwrap().is_empty());
+
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--duplicates",
+            "--key",
+            "customer_id,order_date",
+            "--key",
+            "order_date",
+        ])
+        .unwrap();
+        let configs = args.duplicate_configs(&headers).unwrap();
+        assert_eq!(configs.len(), 3);
+        assert_eq!(configs[0].key_columns, None);
+        assert_eq!(configs[1].key_columns, Some(vec![0, 1]));
+        assert_eq!(configs[2].key_columns, Some(vec![1]));
+
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--key", "missing"]).unwrap();
+        assert!(args.duplicate_configs(&headers).is_err());
+    }
+
+    #[test]
+    fn test_dialect_overrides() {
+        let detected = Dialect::default();
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
+        assert_eq!(args.dialect.apply_dialect_overrides(detected), detected);
+
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--delimiter",
+            "\\t",
+            "--quote",
+            "'",
+            "--no-header",
+        ])
+        .unwrap();
+        let dialect = args.dialect.apply_dialect_overrides(detected);
+        assert_eq!(dialect.delimiter, b'\t');
+        assert_eq!(dialect.quote, b'\'');
+        assert!(!dialect.has_headers);
+
+        assert!(
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--delimiter", ";;"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_forced_header() {
+        // Years as column names over numeric columns look like data to the sniffer
+        let data = "2023,2024\n10,20\n30,40\n50,60\n";
+        let (sniffed, _) =
+            true_sight_csv::sniff_reader(Box::new(std::io::Cursor::new(data))).unwrap();
+        assert!(!sniffed.has_headers);
+
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--header"]).unwrap();
+        assert!(args.dialect.apply_dialect_overrides(sniffed).has_headers);
+
+        assert!(
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--header", "--no-header"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_multiple_inputs() {
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "a.csv", "b.csv.gz", "-", "parts/"]).unwrap();
+        assert_eq!(args.inputs.len(), 4);
+
+        // At least one input is required
+        assert!(TrueSightCsvArgs::try_parse_from(["prog"]).is_err());
+    }
+
+    #[test]
+    fn test_findings_config() {
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv"]).unwrap();
+        assert!(args.findings_config().is_none());
+
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--findings-out", "out.jsonl"])
+                .unwrap();
+        let (_path, config) = args.findings_config().unwrap();
+        assert_eq!(config.format, FindingsFormat::Ndjson);
+        assert_eq!(config.max_per_check_column, 1000);
+
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--findings-out",
+            "out.jsonl",
+            "--findings-format",
+            "csv",
+            "--findings-cap",
+            "0",
+        ])
+        .unwrap();
+        let (_path, config) = args.findings_config().unwrap();
+        assert_eq!(config.format, FindingsFormat::Csv);
+        assert_eq!(config.max_per_check_column, 0);
+    }
+
+    #[test]
+    fn test_quarantine_config() {
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--quarantine-dir",
+            "out",
+            "--reject-on",
+            "null",
+            "--reject-on",
+            "empty",
+        ])
+        .unwrap();
+        let config = args.quarantine_config().unwrap();
+        assert_eq!(config.dir, PathBuf::from("out"));
+        assert_eq!(
+            config.reject_on,
+            vec![NullLikeCheck::NAME, EmptyCheck::NAME]
+        );
+
+        // --reject-on only makes sense with a quarantine directory
+        assert!(
+            TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--reject-on", "null"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_fix_subcommand() {
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "fix",
+            "data.csv",
+            "-o",
+            "clean.csv",
+            "--null-as",
+            "\\N",
+            "--line-ending",
+            "crlf",
+        ])
+        .unwrap();
+        let Some(Command::Fix(fix)) = args.command else {
+            panic!("expected the fix subcommand");
+        };
+        assert_eq!(fix.input, PathBuf::from("data.csv"));
+        let options = fix.fix_options(NullLikeCheck::new());
+        assert_eq!(options.null_value, "\\N");
+        assert_eq!(options.line_terminator, LineTerminator::CrLf);
+        assert!(options.drop_trailing_column);
+
+        let args =
+            TrueSightCsvArgs::try_parse_from(["prog", "fix", "data.csv", "-o", "-"]).unwrap();
+        let Some(Command::Fix(fix)) = args.command else {
+            panic!("expected the fix subcommand");
+        };
+        assert_eq!(fix.fix_options(NullLikeCheck::new()).null_value, "");
+
+        // The dialect and chunk options are the same ones the checks take
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "fix",
+            "data.csv",
+            "-o",
+            "-",
+            "--delimiter",
+            ";",
+            "--encoding",
+            "latin1",
+            "--disable-parallel",
+        ])
+        .unwrap();
+        let Some(Command::Fix(fix)) = args.command else {
+            panic!("expected the fix subcommand");
+        };
+        assert_eq!(fix.dialect.delimiter, Some(b';'));
+        assert_eq!(fix.dialect.encoding, Some(encoding_rs::WINDOWS_1252));
+        assert!(!fix.chunks.is_parallel_enabled());
+
+        // The checks still need an input when no subcommand is given
+        assert!(TrueSightCsvArgs::try_parse_from(["prog"]).is_err());
+    }
+
+    #[test]
+    fn test_null_config() {
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--null-values",
+            "\\N,null,(null)",
+            "--null-column",
+            "ship_date=1900-01-01, 0000-00-00",
+            "--null-case-sensitive",
+        ])
+        .unwrap();
+        let mut config = NullConfig::default();
+        args.nulls.apply(&mut config).unwrap();
+        assert_eq!(
+            config.values,
+            Some(vec![
+                "\\N".to_string(),
+                "null".to_string(),
+                "(null)".to_string()
+            ])
+        );
+        assert!(config.case_sensitive);
+        assert_eq!(
+            config.columns["ship_date"],
+            vec!["1900-01-01", "0000-00-00"]
+        );
+
+        let args = TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--null-column", "email"])
+            .unwrap();
+        assert!(args.nulls.apply(&mut NullConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_regex_rules() {
+        let args = TrueSightCsvArgs::try_parse_from([
+            "prog",
+            "data.csv",
+            "--regex",
+            "email=@email",
+            "--regex",
+            r"shipping_zip=^\d{5}(-\d{4})?$",
+        ])
+        .unwrap();
+
+        let mut columns = BTreeMap::new();
+        columns.insert(
+            "email".to_string(),
+            ColumnRules {
+                required: true,
+                regex: Some(".+".to_string()),
+                ..Default::default()
+            },
+        );
+        args.apply_regex_rules(&mut columns).unwrap();
+        assert!(columns["email"].required);
+        assert_eq!(columns["email"].regex.as_deref(), Some("@email"));
+        assert_eq!(
+            columns["shipping_zip"].regex.as_deref(),
+            Some(r"^\d{5}(-\d{4})?$")
+        );
     }
 
     #[test]
@@ -74,16 +810,16 @@ mod tests {
         let args =
             TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--row-chunk-size", "500000"])
                 .unwrap();
-        assert_eq!(args.row_chunk_size, 500_000);
-        assert!(!args.disable_parallel); // Should still be default
+        assert_eq!(args.chunks.row_chunk_size, 500_000);
+        assert!(!args.chunks.disable_parallel); // Should still be default
     }
 
     #[test]
     fn test_disable_parallel() {
         let args =
             TrueSightCsvArgs::try_parse_from(["prog", "data.csv", "--disable-parallel"]).unwrap();
-        assert_eq!(args.row_chunk_size, 1_000_000); // Should still be default
-        assert!(args.disable_parallel);
+        assert_eq!(args.chunks.row_chunk_size, 1_000_000); // Should still be default
+        assert!(args.chunks.disable_parallel);
     }
 
     #[test]
@@ -96,7 +832,7 @@ mod tests {
             "--disable-parallel",
         ])
         .unwrap();
-        assert_eq!(args.row_chunk_size, 100);
-        assert!(args.disable_parallel);
+        assert_eq!(args.chunks.row_chunk_size, 100);
+        assert!(args.chunks.disable_parallel);
     }
 }
@@ -0,0 +1,96 @@
+use crate::{ColumnRules, NullConfig};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// truesight.toml, every table is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub nulls: NullConfig,
+    #[serde(default)]
+    pub columns: BTreeMap<String, ColumnRules>, // per-column rules, by name or index
+    #[serde(default)]
+    pub rows: BTreeMap<String, String>, // row rules by name, e.g. `positive = "qty * price >= 0"`
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Can't read config file {}: {}", path.display(), e))?;
+        Self::parse(&text)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e).into())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+}
+
+// Config file name picked up next to the data when --config isn't given
+pub const CONFIG_FILE_NAME: &str = "truesight.toml";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_column_rules() {
+        let config = ConfigFile::parse(
+            r#"
+            [columns.email]
+            required = true
+            regex = '^\S+@\S+$'
+
+            [columns.3]
+            min = 0
+            unique = true
+
+            [rows]
+            positive_total = "quantity * unit_price >= 0"
+            "#,
+        )
+        .unwrap();
+        assert!(config.columns["email"].required);
+        assert_eq!(config.rows["positive_total"], "quantity * unit_price >= 0");
+        assert_eq!(config.columns["3"].min, Some(0.0));
+        assert!(config.columns["3"].unique);
+        assert!(ConfigFile::parse(
+            "[columns.email]
+requird = true"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_nulls() {
+        let config = ConfigFile::parse(
+            r##"
+            [nulls]
+            values = ["\\N", "null", "#N/A"]
+            case_sensitive = true
+
+            [nulls.columns]
+            shipped = ["1900-01-01"]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(
+            config.nulls.values,
+            Some(vec![
+                "\\N".to_string(),
+                "null".to_string(),
+                "#N/A".to_string()
+            ])
+        );
+        assert!(config.nulls.case_sensitive);
+        assert_eq!(config.nulls.columns["shipped"], vec!["1900-01-01"]);
+
+        assert!(ConfigFile::parse("").unwrap().nulls.values.is_none());
+        assert!(config.columns.is_empty());
+        assert!(ConfigFile::parse("[nulls]\nvalue = []").is_err());
+    }
+}
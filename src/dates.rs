@@ -0,0 +1,482 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+// Order of the day, month and year in the date part of a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DateLayout {
+    IsoDate,       // 2024-01-15
+    SlashYmd,      // 2024/01/15
+    MonthFirst,    // 1/15/2024
+    DayFirst,      // 15/1/2024
+    SlashDmyOrMdy, // 5/1/2024, could be either until the column says otherwise
+    Dotted,        // 15.01.2024
+    DashDmy,       // 15-01-2024
+}
+
+impl DateLayout {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DateLayout::IsoDate => "YYYY-MM-DD",
+            DateLayout::SlashYmd => "YYYY/MM/DD",
+            DateLayout::MonthFirst | DateLayout::SlashDmyOrMdy => "M/D/YYYY",
+            DateLayout::DayFirst => "D/M/YYYY",
+            DateLayout::Dotted => "DD.MM.YYYY",
+            DateLayout::DashDmy => "DD-MM-YYYY",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeLayout {
+    Minutes,  // 08:30
+    Seconds,  // 08:30:00
+    Fraction, // 08:30:00.123
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Zone {
+    None,
+    Utc,    // Z
+    Offset, // +02:00
+}
+
+// The shape of a date or timestamp value, e.g. `YYYY-MM-DDThh:mm:ssZ`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateFormat {
+    pub date: DateLayout,
+    pub time: Option<(char, TimeLayout)>, // separator, `T` or a space
+    pub zone: Zone,
+}
+
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.date.as_str())?;
+        if let Some((separator, time)) = self.time {
+            let time = match time {
+                TimeLayout::Minutes => "hh:mm",
+                TimeLayout::Seconds => "hh:mm:ss",
+                TimeLayout::Fraction => "hh:mm:ss.f",
+            };
+            write!(f, "{}{}", separator, time)?;
+        }
+        match self.zone {
+            Zone::None => Ok(()),
+            Zone::Utc => f.write_str("Z"),
+            Zone::Offset => f.write_str("+hh:mm"),
+        }
+    }
+}
+
+// Dates outside this range are reported as implausible, e.g. 1900-01-01 placeholders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateWindow {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Default for DateWindow {
+    fn default() -> Self {
+        Self {
+            start: NaiveDate::from_ymd_opt(1900, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2100, 12, 31).unwrap(),
+        }
+    }
+}
+
+impl DateWindow {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+impl fmt::Display for DateWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+// `2000-01-01..2030-12-31`, either side can be left out to keep the default
+impl FromStr for DateWindow {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value
+            .split_once("..")
+            .ok_or_else(|| format!("Expected START..END, got '{}'", value))?;
+        let parse = |date: &str, default: NaiveDate| match date.trim() {
+            "" => Ok(default),
+            date => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Expected a YYYY-MM-DD date, got '{}'", date)),
+        };
+
+        let default = Self::default();
+        let window = Self {
+            start: parse(start, default.start)?,
+            end: parse(end, default.end)?,
+        };
+        if window.start > window.end {
+            return Err(format!("Date window '{}' ends before it starts", value));
+        }
+        Ok(window)
+    }
+}
+
+// A value that has the shape of a date or timestamp
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateObservation {
+    pub format: DateFormat,
+    pub date: Option<NaiveDate>, // None for impossible dates and times, e.g. Feb 30
+    pub in_window: bool,
+}
+
+// Splits a leading run of ASCII digits off a value
+fn take_digits(value: &str) -> (Option<u32>, usize, &str) {
+    let len = value.bytes().take_while(u8::is_ascii_digit).count();
+    let number = if (1..=4).contains(&len) {
+        value[..len].parse().ok()
+    } else {
+        None
+    };
+    (number, len, &value[len..])
+}
+
+// Layout, year, month and day of a leading date, plus what follows it
+fn parse_date_part(value: &str) -> Option<(DateLayout, u32, u32, u32, &str)> {
+    let (a, a_len, rest) = take_digits(value);
+    let separator = rest.chars().next()?;
+    let (b, b_len, rest) = take_digits(rest.get(1..)?);
+    let rest = rest.strip_prefix(separator)?;
+    let (c, c_len, rest) = take_digits(rest);
+    let (a, b, c) = (a?, b?, c?);
+
+    let short = |len: usize| (1..=2).contains(&len);
+    let (layout, year, month, day) = match separator {
+        '-' if a_len == 4 && b_len == 2 && c_len == 2 => (DateLayout::IsoDate, a, b, c),
+        '/' if a_len == 4 && short(b_len) && short(c_len) => (DateLayout::SlashYmd, a, b, c),
+        '/' if short(a_len) && short(b_len) && c_len == 4 => {
+            if a > 12 {
+                (DateLayout::DayFirst, c, b, a)
+            } else if b > 12 {
+                (DateLayout::MonthFirst, c, a, b)
+            } else {
+                (DateLayout::SlashDmyOrMdy, c, a, b)
+            }
+        }
+        '.' if short(a_len) && short(b_len) && c_len == 4 => (DateLayout::Dotted, c, b, a),
+        '-' if short(a_len) && short(b_len) && c_len == 4 => (DateLayout::DashDmy, c, b, a),
+        _ => return None,
+    };
+    Some((layout, year, month, day, rest))
+}
+
+// `08:30`, `08:30:00` or `08:30:00.123`, returns whether the time exists
+fn parse_time_part(value: &str) -> Option<(TimeLayout, bool, &str)> {
+    let two_digits = |value: &str| -> Option<u32> {
+        let (number, len, _) = take_digits(value);
+        (len == 2).then_some(number).flatten()
+    };
+    let hour = two_digits(value)?;
+    let rest = value[2..].strip_prefix(':')?;
+    let minute = two_digits(rest)?;
+    let mut rest = &rest[2..];
+
+    let mut layout = TimeLayout::Minutes;
+    let mut second = 0;
+    if let Some(seconds) = rest.strip_prefix(':') {
+        second = two_digits(seconds)?;
+        layout = TimeLayout::Seconds;
+        rest = &seconds[2..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return None;
+            }
+            layout = TimeLayout::Fraction;
+            rest = &fraction[len..];
+        }
+    }
+
+    // 60 seconds is a leap second
+    let exists = NaiveTime::from_hms_opt(hour, minute, second.min(59)).is_some() && second <= 60;
+    Some((layout, exists, rest))
+}
+
+// `Z`, `+02:00`, `+0200` or `+02`
+fn parse_zone(value: &str) -> Option<Zone> {
+    match value {
+        "" => Some(Zone::None),
+        "Z" | "z" => Some(Zone::Utc),
+        _ => {
+            let offset = value.strip_prefix(['+', '-'])?;
+            let digits: String = offset.chars().filter(|c| *c != ':').collect();
+            let well_formed = matches!(offset.len(), 2 | 4 | 5)
+                && matches!(digits.len(), 2 | 4)
+                && digits.bytes().all(|b| b.is_ascii_digit());
+            well_formed.then_some(Zone::Offset)
+        }
+    }
+}
+
+// Recognises the date shape of a value even when the date itself doesn't exist,
+// so `2/30/2024` is an impossible date instead of just a string
+pub fn observe_date(value: &str, window: &DateWindow) -> Option<DateObservation> {
+    let value = value.trim();
+    if value.len() < 8 || !value.as_bytes()[0].is_ascii_digit() {
+        return None;
+    }
+
+    let (layout, year, month, day, rest) = parse_date_part(value)?;
+    let (time, time_exists, rest) = match rest.chars().next() {
+        None => (None, true, rest),
+        Some(separator @ ('T' | ' ')) => {
+            let (time, exists, rest) = parse_time_part(&rest[1..])?;
+            (Some((separator, time)), exists, rest)
+        }
+        Some(_) => return None,
+    };
+    let zone = parse_zone(rest)?;
+    // A zone without a time is something else, like a version number
+    if time.is_none() && zone != Zone::None {
+        return None;
+    }
+
+    let date = NaiveDate::from_ymd_opt(year as i32, month, day).filter(|_| time_exists);
+    Some(DateObservation {
+        format: DateFormat {
+            date: layout,
+            time,
+            zone,
+        },
+        date,
+        in_window: date.is_none_or(|date| window.contains(date)),
+    })
+}
+
+// Mergeable per-column tally of date formats and problems
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateProfile {
+    formats: BTreeMap<DateFormat, u64>,
+    impossible: u64,
+    with_zone: u64,    // timestamps with Z or an offset
+    without_zone: u64, // timestamps without one, dates don't count
+    outside_window: u64,
+    min: Option<NaiveDate>,
+    max: Option<NaiveDate>,
+}
+
+impl DateProfile {
+    pub fn observe(&mut self, observation: &DateObservation) {
+        let format = observation.format;
+        *self.formats.entry(format).or_insert(0) += 1;
+
+        if format.time.is_some() {
+            if format.zone == Zone::None {
+                self.without_zone += 1;
+            } else {
+                self.with_zone += 1;
+            }
+        }
+
+        match observation.date {
+            Some(date) => {
+                if !observation.in_window {
+                    self.outside_window += 1;
+                }
+                self.min = Some(self.min.map_or(date, |min| min.min(date)));
+                self.max = Some(self.max.map_or(date, |max| max.max(date)));
+            }
+            None => self.impossible += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &DateProfile) {
+        for (format, count) in &other.formats {
+            *self.formats.entry(*format).or_insert(0) += count;
+        }
+        self.impossible += other.impossible;
+        self.with_zone += other.with_zone;
+        self.without_zone += other.without_zone;
+        self.outside_window += other.outside_window;
+        self.min = self.min.into_iter().chain(other.min).min();
+        self.max = self.max.into_iter().chain(other.max).max();
+    }
+
+    // Number of date-like values
+    pub fn total(&self) -> u64 {
+        self.formats.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formats.is_empty()
+    }
+
+    // Formats with their counts, most common first. Slash dates that work both ways
+    // count as day first when the column only has day first dates otherwise
+    pub fn formats(&self) -> Vec<(String, u64)> {
+        let day_first = self.formats.keys().any(|f| f.date == DateLayout::DayFirst)
+            && !self
+                .formats
+                .keys()
+                .any(|f| f.date == DateLayout::MonthFirst);
+
+        let mut formats: BTreeMap<DateFormat, u64> = BTreeMap::new();
+        for (format, count) in &self.formats {
+            let mut format = *format;
+            if format.date == DateLayout::SlashDmyOrMdy {
+                format.date = if day_first {
+                    DateLayout::DayFirst
+                } else {
+                    DateLayout::MonthFirst
+                };
+            }
+            *formats.entry(format).or_insert(0) += count;
+        }
+
+        let mut formats: Vec<(String, u64)> = formats
+            .into_iter()
+            .map(|(format, count)| (format.to_string(), count))
+            .collect();
+        formats.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        formats
+    }
+
+    pub fn mixed_formats(&self) -> bool {
+        self.formats().len() > 1
+    }
+
+    pub fn mixed_zones(&self) -> bool {
+        self.with_zone > 0 && self.without_zone > 0
+    }
+
+    pub fn impossible(&self) -> u64 {
+        self.impossible
+    }
+
+    pub fn with_zone(&self) -> u64 {
+        self.with_zone
+    }
+
+    pub fn without_zone(&self) -> u64 {
+        self.without_zone
+    }
+
+    pub fn outside_window(&self) -> u64 {
+        self.outside_window
+    }
+
+    pub fn min(&self) -> Option<NaiveDate> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<NaiveDate> {
+        self.max
+    }
+
+    // Short descriptions of everything that looks wrong with the column
+    pub fn issues(&self) -> Vec<&'static str> {
+        let mut issues = Vec::new();
+        if self.mixed_formats() {
+            issues.push("mixed formats");
+        }
+        if self.impossible > 0 {
+            issues.push("impossible dates");
+        }
+        if self.mixed_zones() {
+            issues.push("mixed time zones");
+        }
+        if self.outside_window > 0 {
+            issues.push("outside window");
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_of(value: &str) -> Option<String> {
+        observe_date(value, &DateWindow::default()).map(|o| o.format.to_string())
+    }
+
+    #[test]
+    fn test_observe_date_formats() {
+        assert_eq!(format_of("2024-01-15").as_deref(), Some("YYYY-MM-DD"));
+        assert_eq!(format_of("1/15/2024").as_deref(), Some("M/D/YYYY"));
+        assert_eq!(format_of("15/1/2024").as_deref(), Some("D/M/YYYY"));
+        assert_eq!(format_of("15.01.2024").as_deref(), Some("DD.MM.YYYY"));
+        assert_eq!(
+            format_of("2024-01-15T08:30:00Z").as_deref(),
+            Some("YYYY-MM-DDThh:mm:ssZ")
+        );
+        assert_eq!(
+            format_of("2024-01-15 08:30:00.5+02:00").as_deref(),
+            Some("YYYY-MM-DD hh:mm:ss.f+hh:mm")
+        );
+        assert_eq!(
+            format_of("2024-01-15T08:30").as_deref(),
+            Some("YYYY-MM-DDThh:mm")
+        );
+        assert_eq!(format_of("SKU12345"), None);
+        assert_eq!(format_of("12345678"), None);
+        assert_eq!(format_of("1.2.3-beta"), None);
+        assert_eq!(format_of("2024-01-15 lunch"), None);
+
+        let window = DateWindow::default();
+        assert_eq!(observe_date("2/30/2024", &window).unwrap().date, None);
+        assert_eq!(
+            observe_date("2024-01-15T25:00", &window).unwrap().date,
+            None
+        );
+        assert!(!observe_date("1800-01-01", &window).unwrap().in_window);
+    }
+
+    #[test]
+    fn test_date_profile() {
+        let window: DateWindow = "2000-01-01..".parse().unwrap();
+        let mut first = DateProfile::default();
+        let mut second = DateProfile::default();
+        for value in [
+            "1/15/2024",
+            "2/30/2024",
+            "1999-12-31",
+            "2024-01-15T08:30:00Z",
+        ] {
+            first.observe(&observe_date(value, &window).unwrap());
+        }
+        for value in ["15.01.2024", "2024-01-16T08:30:00"] {
+            second.observe(&observe_date(value, &window).unwrap());
+        }
+        first.merge(&second);
+
+        assert_eq!(first.total(), 6);
+        assert_eq!(first.formats()[0], ("M/D/YYYY".to_string(), 2));
+        assert_eq!(first.impossible(), 1);
+        assert_eq!(first.outside_window(), 1);
+        assert!(first.mixed_zones());
+        assert_eq!(first.min(), NaiveDate::from_ymd_opt(1999, 12, 31));
+        assert_eq!(first.max(), NaiveDate::from_ymd_opt(2024, 1, 16));
+        assert_eq!(
+            first.issues(),
+            vec![
+                "mixed formats",
+                "impossible dates",
+                "mixed time zones",
+                "outside window"
+            ]
+        );
+
+        // 5/1/2024 fits a day first column
+        let mut day_first = DateProfile::default();
+        for value in ["25/12/2024", "5/1/2024"] {
+            day_first.observe(&observe_date(value, &window).unwrap());
+        }
+        assert_eq!(day_first.formats(), vec![("D/M/YYYY".to_string(), 2)]);
+        assert!(day_first.issues().is_empty());
+
+        assert!("2030-01-01..2000-01-01".parse::<DateWindow>().is_err());
+        assert!("2000-01-01".parse::<DateWindow>().is_err());
+    }
+}
@@ -0,0 +1,434 @@
+use crate::ChunkObserver;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Fingerprints are spread over this many files in spill mode, each one is
+// loaded into memory on its own when the groups are resolved
+const SPILL_PARTITIONS: usize = 64;
+
+// 16 byte fingerprint + 8 byte line number
+const SPILL_ENTRY_SIZE: usize = 24;
+
+// Several detectors can spill into the same directory, e.g. --duplicates and `unique`
+// rules, so every detector in the process gets its own file prefix
+static NEXT_SPILL_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+pub struct DuplicateConfig {
+    pub key_columns: Option<Vec<usize>>, // None compares whole rows
+    pub max_lines_per_group: usize,
+    pub max_groups_reported: usize,
+    pub spill_dir: Option<PathBuf>, // Write fingerprints to disk instead of holding them in memory
+}
+
+impl Default for DuplicateConfig {
+    fn default() -> Self {
+        Self {
+            key_columns: None,
+            max_lines_per_group: 5,
+            max_groups_reported: 50,
+            spill_dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub occurrences: u64,
+    pub line_numbers: Vec<u64>, // first N lines of the group in file order
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReport {
+    pub key_columns: Option<Vec<String>>, // None when whole rows were compared
+    pub duplicate_rows: u64,              // rows repeating an earlier row (or key)
+    pub duplicate_groups: u64,
+    pub groups: Vec<DuplicateGroup>, // ordered by first line, capped at max_groups_reported
+}
+
+// In memory we only keep the first line for unique fingerprints and
+// promote them to a group once a second occurrence shows up
+#[derive(Default)]
+struct InMemoryState {
+    first_seen: HashMap<u128, u64>,
+    groups: HashMap<u128, DuplicateGroup>,
+}
+
+struct SpillState {
+    paths: Vec<PathBuf>,
+    writers: Vec<BufWriter<File>>,
+}
+
+enum FingerprintStore {
+    InMemory(InMemoryState),
+    Spill(SpillState),
+}
+
+// Counts duplicate rows (or composite keys) across the whole file using 128 bit
+// fingerprints, so unlike the per-cell checks it has to see every chunk in order.
+pub struct DuplicateDetector {
+    config: DuplicateConfig,
+    key_names: Option<Vec<String>>,
+    store: FingerprintStore,
+    report: Option<DuplicateReport>,
+}
+
+impl DuplicateDetector {
+    pub fn new(config: DuplicateConfig, headers: &[String]) -> Result<Self, Box<dyn Error>> {
+        let key_names = config.key_columns.as_ref().map(|columns| {
+            columns
+                .iter()
+                .map(|&col| {
+                    headers
+                        .get(col)
+                        .cloned()
+                        .unwrap_or_else(|| format!("col_{}", col))
+                })
+                .collect()
+        });
+
+        let store = match &config.spill_dir {
+            Some(dir) => FingerprintStore::Spill(SpillState::create(dir)?),
+            None => FingerprintStore::InMemory(InMemoryState::default()),
+        };
+
+        Ok(Self {
+            config,
+            key_names,
+            store,
+            report: None,
+        })
+    }
+
+    // Resolve `--key` column names against the headers
+    pub fn resolve_key_columns(
+        key_names: &[String],
+        headers: &[String],
+    ) -> Result<Vec<usize>, Box<dyn Error>> {
+        key_names
+            .iter()
+            .map(|name| {
+                headers
+                    .iter()
+                    .position(|header| header == name)
+                    .ok_or_else(|| {
+                        format!("Duplicate key refers to unknown column '{}'", name).into()
+                    })
+            })
+            .collect()
+    }
+
+    fn fingerprint(&self, record: &csv::StringRecord) -> u128 {
+        // Two 64 bit SipHashes of the same fields, the second one salted with a leading
+        // byte so the halves differ. Collisions are negligible at 128 bits, and the spill
+        // partition is taken from the top bits of the high half
+        let mut low = DefaultHasher::new();
+        let mut high = DefaultHasher::new();
+        high.write_u8(0x5a);
+
+        let mut hash_field = |field: &str| {
+            field.hash(&mut low);
+            field.hash(&mut high);
+        };
+
+        match &self.config.key_columns {
+            Some(columns) => columns
+                .iter()
+                .for_each(|&col| hash_field(record.get(col).unwrap_or(""))),
+            None => record.iter().for_each(&mut hash_field),
+        }
+
+        ((high.finish() as u128) << 64) | low.finish() as u128
+    }
+
+    // Available once processing has finished
+    pub fn report(&self) -> Option<&DuplicateReport> {
+        self.report.as_ref()
+    }
+
+    fn build_report(&self, groups: Vec<DuplicateGroup>) -> DuplicateReport {
+        let duplicate_rows = groups.iter().map(|group| group.occurrences - 1).sum();
+        let duplicate_groups = groups.len() as u64;
+
+        let mut groups = groups;
+        groups.sort_by_key(|group| group.line_numbers.first().copied().unwrap_or(0));
+        groups.truncate(self.config.max_groups_reported);
+
+        DuplicateReport {
+            key_columns: self.key_names.clone(),
+            duplicate_rows,
+            duplicate_groups,
+            groups,
+        }
+    }
+}
+
+impl InMemoryState {
+    fn insert(&mut self, fingerprint: u128, line: u64, max_lines: usize) {
+        if let Some(group) = self.groups.get_mut(&fingerprint) {
+            group.occurrences += 1;
+            if group.line_numbers.len() < max_lines {
+                group.line_numbers.push(line);
+            }
+        } else if let Some(first_line) = self.first_seen.remove(&fingerprint) {
+            let mut line_numbers = vec![first_line, line];
+            line_numbers.truncate(max_lines);
+            self.groups.insert(
+                fingerprint,
+                DuplicateGroup {
+                    occurrences: 2,
+                    line_numbers,
+                },
+            );
+        } else {
+            self.first_seen.insert(fingerprint, line);
+        }
+    }
+}
+
+impl SpillState {
+    fn create(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let spill_id = NEXT_SPILL_ID.fetch_add(1, Ordering::Relaxed);
+        let mut paths = Vec::with_capacity(SPILL_PARTITIONS);
+        let mut writers = Vec::with_capacity(SPILL_PARTITIONS);
+        for partition in 0..SPILL_PARTITIONS {
+            let path = dir.join(format!(
+                "true-sight-dups-{}-{}-{}.bin",
+                std::process::id(),
+                spill_id,
+                partition
+            ));
+            writers.push(BufWriter::new(File::create(&path)?));
+            paths.push(path);
+        }
+        Ok(Self { paths, writers })
+    }
+
+    fn insert(&mut self, fingerprint: u128, line: u64) -> Result<(), Box<dyn Error>> {
+        let partition = (fingerprint >> 122) as usize % SPILL_PARTITIONS;
+        let writer = &mut self.writers[partition];
+        writer.write_all(&fingerprint.to_le_bytes())?;
+        writer.write_all(&line.to_le_bytes())?;
+        Ok(())
+    }
+
+    // Load one partition at a time, sort by fingerprint and collect the groups
+    fn resolve(&mut self, max_lines: usize) -> Result<Vec<DuplicateGroup>, Box<dyn Error>> {
+        for writer in &mut self.writers {
+            writer.flush()?;
+        }
+        self.writers.clear();
+
+        let mut groups = Vec::new();
+        for path in &self.paths {
+            let mut entries = Vec::new();
+            let mut reader = BufReader::new(File::open(path)?);
+            let mut buf = [0u8; SPILL_ENTRY_SIZE];
+            loop {
+                match reader.read_exact(&mut buf) {
+                    Ok(()) => {
+                        let fingerprint = u128::from_le_bytes(buf[..16].try_into().unwrap());
+                        let line = u64::from_le_bytes(buf[16..].try_into().unwrap());
+                        entries.push((fingerprint, line));
+                    }
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            entries.sort_unstable();
+
+            for run in entries
+                .chunk_by(|a, b| a.0 == b.0)
+                .filter(|run| run.len() > 1)
+            {
+                groups.push(DuplicateGroup {
+                    occurrences: run.len() as u64,
+                    line_numbers: run.iter().take(max_lines).map(|(_, line)| *line).collect(),
+                });
+            }
+        }
+
+        self.cleanup();
+        Ok(groups)
+    }
+
+    fn cleanup(&mut self) {
+        self.writers.clear();
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl Drop for SpillState {
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl ChunkObserver for DuplicateDetector {
+    fn observe_chunk(&mut self, records: &[csv::StringRecord]) -> Result<(), Box<dyn Error>> {
+        for record in records {
+            let fingerprint = self.fingerprint(record);
+            let line = record.position().map(|pos| pos.line()).unwrap_or(0);
+            match &mut self.store {
+                FingerprintStore::InMemory(state) => {
+                    state.insert(fingerprint, line, self.config.max_lines_per_group)
+                }
+                FingerprintStore::Spill(state) => state.insert(fingerprint, line)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        let max_lines = self.config.max_lines_per_group;
+        let groups = match &mut self.store {
+            FingerprintStore::InMemory(state) => {
+                state.first_seen = HashMap::new();
+                std::mem::take(&mut state.groups).into_values().collect()
+            }
+            FingerprintStore::Spill(state) => state.resolve(max_lines)?,
+        };
+        self.report = Some(self.build_report(groups));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(rows: &[&[&str]]) -> Vec<csv::StringRecord> {
+        let data: String = rows.iter().map(|row| row.join(",") + "\n").collect();
+        csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes())
+            .records()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn run(config: DuplicateConfig, chunks: &[Vec<csv::StringRecord>]) -> DuplicateReport {
+        let headers = vec!["id".to_string(), "date".to_string(), "sku".to_string()];
+        let mut detector = DuplicateDetector::new(config, &headers).unwrap();
+        for chunk in chunks {
+            detector.observe_chunk(chunk).unwrap();
+        }
+        detector.finish().unwrap();
+        detector.report().unwrap().clone()
+    }
+
+    fn sample() -> Vec<Vec<csv::StringRecord>> {
+        let all = records(&[
+            &["1", "2024-01-01", "A"],
+            &["2", "2024-01-01", "B"],
+            &["1", "2024-01-01", "A"],
+            &["1", "2024-01-01", "C"],
+            &["1", "2024-01-01", "A"],
+            &["2", "2024-01-02", "B"],
+        ]);
+        // Split across chunks to make sure duplicates are found across chunk boundaries
+        vec![all[..2].to_vec(), all[2..].to_vec()]
+    }
+
+    #[test]
+    fn test_full_row_duplicates() {
+        let report = run(DuplicateConfig::default(), &sample());
+        assert_eq!(report.key_columns, None);
+        assert_eq!(report.duplicate_groups, 1);
+        assert_eq!(report.duplicate_rows, 2);
+        assert_eq!(report.groups[0].occurrences, 3);
+        assert_eq!(report.groups[0].line_numbers, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn test_key_duplicates_with_line_cap() {
+        let config = DuplicateConfig {
+            key_columns: Some(vec![0, 1]),
+            max_lines_per_group: 2,
+            ..Default::default()
+        };
+        let report = run(config, &sample());
+        assert_eq!(
+            report.key_columns,
+            Some(vec!["id".to_string(), "date".to_string()])
+        );
+        assert_eq!(report.duplicate_groups, 1);
+        assert_eq!(report.duplicate_rows, 3);
+        assert_eq!(report.groups[0].occurrences, 4);
+        assert_eq!(report.groups[0].line_numbers, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_spill_matches_in_memory() {
+        let dir = std::env::temp_dir().join(format!("true-sight-dup-test-{}", std::process::id()));
+        let config = DuplicateConfig {
+            key_columns: Some(vec![0]),
+            spill_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        let spilled = run(config, &sample());
+        let in_memory = run(
+            DuplicateConfig {
+                key_columns: Some(vec![0]),
+                ..Default::default()
+            },
+            &sample(),
+        );
+
+        assert_eq!(spilled.duplicate_rows, in_memory.duplicate_rows);
+        assert_eq!(spilled.duplicate_groups, 2);
+        assert_eq!(spilled.groups[0].line_numbers, vec![1, 3, 4, 5]);
+        assert_eq!(spilled.groups[1].line_numbers, vec![2, 6]);
+        assert_eq!(in_memory.groups[0].line_numbers, vec![1, 3, 4, 5]);
+
+        // Spill files are removed once the groups are resolved
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_detectors_spill_into_the_same_dir() {
+        let dir =
+            std::env::temp_dir().join(format!("true-sight-dup-shared-{}", std::process::id()));
+        let headers = vec!["id".to_string(), "date".to_string(), "sku".to_string()];
+        let spill = |key_columns| DuplicateConfig {
+            key_columns,
+            spill_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        let mut by_row = DuplicateDetector::new(spill(None), &headers).unwrap();
+        let mut by_key = DuplicateDetector::new(spill(Some(vec![0])), &headers).unwrap();
+
+        for chunk in sample() {
+            by_row.observe_chunk(&chunk).unwrap();
+            by_key.observe_chunk(&chunk).unwrap();
+        }
+        by_row.finish().unwrap();
+        by_key.finish().unwrap();
+
+        assert_eq!(by_row.report().unwrap().duplicate_rows, 2);
+        assert_eq!(by_key.report().unwrap().duplicate_rows, 4);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_key_columns() {
+        let headers = vec!["customer_id".to_string(), "order_date".to_string()];
+        let keys = vec!["order_date".to_string(), "customer_id".to_string()];
+        assert_eq!(
+            DuplicateDetector::resolve_key_columns(&keys, &headers).unwrap(),
+            vec![1, 0]
+        );
+        assert!(DuplicateDetector::resolve_key_columns(&["nope".to_string()], &headers).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::input::InputReader;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io::{self, Cursor, Read};
+
+// Bytes looked at to guess the encoding, before the dialect is sniffed
+pub const ENCODING_SAMPLE_BYTES: usize = 64 * 1024;
+
+// How the input was turned into the UTF-8 the csv reader sees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InputEncoding {
+    #[serde(rename = "name", serialize_with = "serialize_encoding")]
+    pub encoding: &'static Encoding,
+    pub bom: bool,      // a byte order mark was found and stripped
+    pub detected: bool, // false when it was given with --encoding
+}
+
+fn serialize_encoding<S: Serializer>(
+    encoding: &&'static Encoding,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(encoding.name())
+}
+
+impl InputEncoding {
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+}
+
+impl Default for InputEncoding {
+    fn default() -> Self {
+        Self {
+            encoding: UTF_8,
+            bom: false,
+            detected: true,
+        }
+    }
+}
+
+impl fmt::Display for InputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, BOM: {}",
+            self.name(),
+            if self.bom { "yes" } else { "no" }
+        )
+    }
+}
+
+// WHATWG labels, e.g. `utf-8`, `latin1`, `windows-1252` or `utf-16le`. Latin-1 is read
+// as Windows-1252 like browsers do, it only differs in the unused C1 control range
+pub fn parse_encoding_label(label: &str) -> Result<&'static Encoding, String> {
+    match Encoding::for_label(label.trim().as_bytes()) {
+        Some(encoding) if encoding != encoding_rs::REPLACEMENT => Ok(encoding),
+        _ => Err(format!(
+            "unknown encoding '{}', expected e.g. utf-8, latin1, windows-1252, utf-16le or utf-16be",
+            label
+        )),
+    }
+}
+
+// Guess the encoding of a sample: a BOM wins, then UTF-16 without one (every other
+// byte NUL for ASCII text), then UTF-8. Anything else is taken for Windows-1252 unless
+// it also holds valid multi-byte UTF-8, then it's UTF-8 with a few bad bytes.
+pub fn detect_encoding(sample: &[u8]) -> (&'static Encoding, bool) {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return (encoding, true);
+    }
+    if let Some(encoding) = utf16_without_bom(sample) {
+        return (encoding, false);
+    }
+
+    let mut multi_byte = 0;
+    let mut invalid = 0;
+    for chunk in sample.utf8_chunks() {
+        multi_byte += chunk.valid().chars().filter(|c| !c.is_ascii()).count();
+        // A sequence cut off at the end of the sample is still UTF-8
+        if !chunk.invalid().is_empty()
+            && chunk.invalid().as_ptr_range().end < sample.as_ptr_range().end
+        {
+            invalid += 1;
+        }
+    }
+    if invalid > 0 && multi_byte == 0 {
+        (WINDOWS_1252, false)
+    } else {
+        (UTF_8, false)
+    }
+}
+
+fn utf16_without_bom(sample: &[u8]) -> Option<&'static Encoding> {
+    let pairs = sample.len().min(4096) / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let (mut even_nuls, mut odd_nuls) = (0, 0);
+    for pair in sample[..pairs * 2].chunks_exact(2) {
+        even_nuls += (pair[0] == 0) as usize;
+        odd_nuls += (pair[1] == 0) as usize;
+    }
+    if odd_nuls * 2 > pairs && even_nuls * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_nuls * 2 > pairs && odd_nuls * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+// Detect (or take) the encoding and transcode the input to UTF-8 while it is read.
+// UTF-8 is passed through untouched apart from the BOM, so invalid bytes still reach
+// the csv reader and --byte-records can report them.
+pub fn decode_input(
+    mut input: InputReader,
+    forced: Option<&'static Encoding>,
+) -> io::Result<(InputEncoding, InputReader)> {
+    let mut sample = Vec::with_capacity(ENCODING_SAMPLE_BYTES);
+    input
+        .by_ref()
+        .take(ENCODING_SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+
+    let (detected, _) = detect_encoding(&sample);
+    let encoding = forced.unwrap_or(detected);
+    // Only a BOM of the encoding that is used is stripped
+    let bom_len = match Encoding::for_bom(&sample) {
+        Some((bom_encoding, len)) if bom_encoding == encoding => len,
+        _ => 0,
+    };
+    let info = InputEncoding {
+        encoding,
+        bom: bom_len > 0,
+        detected: forced.is_none(),
+    };
+
+    let rest = Cursor::new(sample).chain(input);
+    let input: InputReader = if encoding == UTF_8 {
+        let mut rest = rest;
+        io::copy(&mut rest.by_ref().take(bom_len as u64), &mut io::sink())?;
+        Box::new(rest)
+    } else {
+        Box::new(
+            DecodeReaderBytesBuilder::new()
+                .encoding(Some(encoding))
+                .strip_bom(bom_len > 0)
+                .build(rest),
+        )
+    };
+    Ok((info, input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8], forced: Option<&'static Encoding>) -> (InputEncoding, String) {
+        let (info, mut input) =
+            decode_input(Box::new(Cursor::new(bytes.to_vec())), forced).unwrap();
+        let mut text = String::new();
+        input.read_to_string(&mut text).unwrap();
+        (info, text)
+    }
+
+    fn utf16(text: &str, little_endian: bool, bom: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for unit in bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16()) {
+            let pair = if little_endian {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            };
+            bytes.extend_from_slice(&pair);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_detect_and_transcode() {
+        let text = "id,name\n1,café\n";
+
+        let (info, decoded) = decode(text.as_bytes(), None);
+        assert_eq!((info.name(), info.bom), ("UTF-8", false));
+        assert_eq!(decoded, text);
+
+        let (info, decoded) = decode(
+            &[b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat(),
+            None,
+        );
+        assert_eq!((info.name(), info.bom), ("UTF-8", true));
+        assert_eq!(decoded, text);
+
+        let (info, decoded) = decode(b"id,name\n1,caf\xe9 \x80\n", None);
+        assert_eq!((info.name(), info.bom), ("windows-1252", false));
+        assert_eq!(decoded, "id,name\n1,café €\n");
+
+        let (info, decoded) = decode(&utf16(text, true, true), None);
+        assert_eq!((info.name(), info.bom), ("UTF-16LE", true));
+        assert_eq!(decoded, text);
+
+        let (info, decoded) = decode(&utf16(text, false, false), None);
+        assert_eq!((info.name(), info.bom), ("UTF-16BE", false));
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn test_mostly_utf8_and_forced_encoding() {
+        // Valid UTF-8 next to a stray Latin-1 byte stays UTF-8, the bad byte is kept
+        let bytes = b"id,name\n1,caf\xc3\xa9\n2,caf\xe9\n";
+        let (info, _) = decode_input(Box::new(Cursor::new(bytes.to_vec())), None).unwrap();
+        assert_eq!(info.name(), "UTF-8");
+
+        // A multi-byte character cut off at the end of the sample doesn't count as invalid
+        assert_eq!(detect_encoding(b"id,name\n1,caf\xc3").0, UTF_8);
+
+        let (info, decoded) = decode(b"a\n\xe9\n", Some(parse_encoding_label("latin1").unwrap()));
+        assert!(!info.detected);
+        assert_eq!(decoded, "a\né\n");
+
+        assert!(parse_encoding_label("klingon").is_err());
+    }
+}
@@ -0,0 +1,351 @@
+use crate::utf8::INVALID_UTF8;
+use crate::{CellFinding, CheckRegistry, ChunkObserver, InvalidUtf8Stats};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingsFormat {
+    Csv,
+    Ndjson,
+}
+
+impl FindingsFormat {
+    // `.ndjson` and `.jsonl` files get NDJSON, anything else CSV
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ndjson") | Some("jsonl") => FindingsFormat::Ndjson,
+            _ => FindingsFormat::Csv,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FindingsConfig {
+    pub format: FindingsFormat,
+    pub max_per_check_column: usize, // 0 means no cap
+}
+
+impl Default for FindingsConfig {
+    fn default() -> Self {
+        Self {
+            format: FindingsFormat::Csv,
+            max_per_check_column: 1000,
+        }
+    }
+}
+
+// One offending cell, the line and byte offset come from StringRecord::position()
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding<'a> {
+    pub file: &'a str,
+    pub line_number: u64,
+    pub byte_offset: u64,
+    pub column_index: usize,
+    pub column: &'a str,
+    pub check_name: &'a str,
+    pub raw_value: &'a str,
+}
+
+enum Sink {
+    Csv(Box<csv::Writer<Box<dyn Write>>>),
+    Ndjson(BufWriter<Box<dyn Write>>),
+}
+
+// Writes every cell flagged by a registered check to a CSV or NDJSON file. Runs as a
+// ChunkObserver so the findings come out in file order, and can be reused across inputs.
+// The checks aren't run again here, the workers record the flagged cells while checking.
+pub struct FindingsWriter {
+    sink: Sink,
+    registry: CheckRegistry,
+    config: FindingsConfig,
+    headers: Vec<String>,
+    source: String,
+    counts: HashMap<(usize, usize), usize>, // (check index, column) -> findings seen
+    written: usize,
+    dropped: usize,
+}
+
+impl FindingsWriter {
+    pub fn create(
+        path: &Path,
+        config: FindingsConfig,
+        registry: &CheckRegistry,
+    ) -> Result<Self, Box<dyn Error>> {
+        let file = File::create(path)?;
+        Ok(Self::new(Box::new(file), config, registry))
+    }
+
+    pub fn new(writer: Box<dyn Write>, config: FindingsConfig, registry: &CheckRegistry) -> Self {
+        let sink = match config.format {
+            FindingsFormat::Csv => Sink::Csv(Box::new(csv::Writer::from_writer(writer))),
+            FindingsFormat::Ndjson => Sink::Ndjson(BufWriter::new(writer)),
+        };
+        Self {
+            sink,
+            registry: registry.clone(),
+            config,
+            headers: Vec::new(),
+            source: String::new(),
+            counts: HashMap::new(),
+            written: 0,
+            dropped: 0,
+        }
+    }
+
+    // Call before each input so the findings can be traced back to their file
+    pub fn start_source(&mut self, source: &str, headers: &[String]) {
+        self.source = source.to_string();
+        self.headers = headers.to_vec();
+    }
+
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    // Findings past the per check/column cap
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+impl Sink {
+    fn write(&mut self, finding: &Finding) -> Result<(), Box<dyn Error>> {
+        match self {
+            Sink::Csv(writer) => writer.serialize(finding)?,
+            Sink::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, finding)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ChunkObserver for FindingsWriter {
+    // The flagged cells come from the workers through observe_findings
+    fn observe_chunk(&mut self, _records: &[csv::StringRecord]) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn wants_findings(&self) -> bool {
+        true
+    }
+
+    fn observe_findings(
+        &mut self,
+        records: &[csv::StringRecord],
+        findings: &[CellFinding],
+    ) -> Result<(), Box<dyn Error>> {
+        let cap = self.config.max_per_check_column;
+        let checks = self.registry.checks();
+        let row_checks = self.registry.row_checks();
+
+        for finding in findings {
+            let seen = self
+                .counts
+                .entry((finding.check, finding.column))
+                .or_insert(0);
+            *seen += 1;
+            if cap > 0 && *seen > cap {
+                self.dropped += 1;
+                continue;
+            }
+
+            let record = &records[finding.row];
+            let (line_number, byte_offset) = record
+                .position()
+                .map(|pos| (pos.line(), pos.byte()))
+                .unwrap_or((0, 0));
+            // Row checks follow the cell checks, their value is the one of their first column
+            let check_name = match checks.get(finding.check) {
+                Some(check) => check.name(),
+                None => row_checks[finding.check - checks.len()].name(),
+            };
+
+            self.sink.write(&Finding {
+                file: &self.source,
+                line_number,
+                byte_offset,
+                column_index: finding.column,
+                column: self.headers.get(finding.column).map_or("", |h| h.as_str()),
+                check_name,
+                raw_value: record.get(finding.column).unwrap_or(""),
+            })?;
+            self.written += 1;
+        }
+
+        Ok(())
+    }
+
+    // Invalid cells come after the chunk's other findings, counted past the row checks
+    fn observe_invalid_utf8(&mut self, stats: &InvalidUtf8Stats) -> Result<(), Box<dyn Error>> {
+        let cap = self.config.max_per_check_column;
+        let check_idx = self.registry.len() + self.registry.row_checks().len();
+        for cell in &stats.cells {
+            let seen = self.counts.entry((check_idx, cell.column)).or_insert(0);
+            *seen += 1;
+            if cap > 0 && *seen > cap {
+                self.dropped += 1;
+                continue;
+            }
+
+            self.sink.write(&Finding {
+                file: &self.source,
+                line_number: cell.line,
+                byte_offset: cell.byte_offset,
+                column_index: cell.column,
+                column: self.headers.get(cell.column).map_or("", |h| h.as_str()),
+                check_name: INVALID_UTF8,
+                raw_value: &cell.value,
+            })?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        match &mut self.sink {
+            Sink::Csv(writer) => writer.flush()?,
+            Sink::Ndjson(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyCheck, NullLikeCheck};
+
+    // Check a chunk with findings recorded and hand it over like the processing loop does
+    fn observe(writer: &mut FindingsWriter, records: &[csv::StringRecord]) {
+        let result = crate::check_records(records, 1, &CheckRegistry::new(), false, true);
+        crate::observe_checked_chunk(&mut [writer], records, &result).unwrap();
+    }
+
+    fn run(format: FindingsFormat, cap: usize) -> (String, usize, usize) {
+        let path = std::env::temp_dir().join(format!(
+            "true-sight-findings-{}-{:?}-{}",
+            std::process::id(),
+            format,
+            cap
+        ));
+        let data = "id,email\n1,NULL\n2,\n3,a@b.com\n4,\n";
+        let mut rdr = csv::Reader::from_reader(data.as_bytes());
+        let headers: Vec<String> = rdr.headers().unwrap().iter().map(String::from).collect();
+        let records: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+
+        let config = FindingsConfig {
+            format,
+            max_per_check_column: cap,
+        };
+        let mut writer = FindingsWriter::create(&path, config, &CheckRegistry::new()).unwrap();
+        writer.start_source("data.csv", &headers);
+        observe(&mut writer, &records[..2]);
+        observe(&mut writer, &records[2..]);
+        writer.finish().unwrap();
+
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        (output, writer.written(), writer.dropped())
+    }
+
+    #[test]
+    fn test_csv_findings() {
+        let (output, written, dropped) = run(FindingsFormat::Csv, 0);
+        assert_eq!(written, 3);
+        assert_eq!(dropped, 0);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            "file,line_number,byte_offset,column_index,column,check_name,raw_value"
+        );
+        assert_eq!(
+            lines[1],
+            format!("data.csv,2,9,1,email,{},NULL", NullLikeCheck::NAME)
+        );
+        assert_eq!(
+            lines[2],
+            format!("data.csv,3,16,1,email,{},", EmptyCheck::NAME)
+        );
+        assert!(lines[3].starts_with("data.csv,5,"));
+    }
+
+    #[test]
+    fn test_ndjson_findings_with_cap() {
+        let (output, written, dropped) = run(FindingsFormat::Ndjson, 1);
+        // Only the first empty email is kept
+        assert_eq!(written, 2);
+        assert_eq!(dropped, 1);
+
+        let first: serde_json::Value =
+            serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(first["line_number"], 2);
+        assert_eq!(first["column"], "email");
+        assert_eq!(first["raw_value"], "NULL");
+    }
+
+    #[test]
+    fn test_invalid_utf8_findings() {
+        let data = b"id,name\n1,caf\xe9\n2,ok\n";
+        let mut rdr = csv::Reader::from_reader(&data[..]);
+        let records: Vec<csv::ByteRecord> = rdr.byte_records().map(|r| r.unwrap()).collect();
+        let (records, stats) = crate::utf8::decode_records(records);
+
+        let buffer = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let config = FindingsConfig {
+            format: FindingsFormat::Csv,
+            max_per_check_column: 0,
+        };
+        let mut writer = FindingsWriter::new(
+            Box::new(SharedBuffer(buffer.clone())),
+            config,
+            &CheckRegistry::new(),
+        );
+        writer.start_source("latin1.csv", &["id".to_string(), "name".to_string()]);
+        observe(&mut writer, &records);
+        writer.observe_invalid_utf8(&stats).unwrap();
+        writer.finish().unwrap();
+
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        assert_eq!(writer.written(), 1);
+        assert_eq!(
+            output.lines().nth(1),
+            Some("latin1.csv,2,8,1,name,INVALID_UTF8,caf\u{FFFD}")
+        );
+    }
+
+    // Lets the test read back what the writer wrote
+    struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            FindingsFormat::from_path(Path::new("out.ndjson")),
+            FindingsFormat::Ndjson
+        );
+        assert_eq!(
+            FindingsFormat::from_path(Path::new("out.jsonl")),
+            FindingsFormat::Ndjson
+        );
+        assert_eq!(
+            FindingsFormat::from_path(Path::new("out.csv")),
+            FindingsFormat::Csv
+        );
+    }
+}
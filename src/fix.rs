@@ -0,0 +1,245 @@
+use crate::{CsvChunkIterator, LineTerminator, NullLikeCheck, PatternCheck};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::error::Error;
+use std::io::{Read, Write};
+
+// What the `fix` subcommand changes
+#[derive(Debug, Clone)]
+pub struct FixOptions {
+    pub null_value: String, // canonical null, "" or "\N"
+    pub line_terminator: LineTerminator,
+    pub drop_trailing_column: bool,
+    pub null_check: NullLikeCheck, // decides which cells are NULL-like
+}
+
+impl Default for FixOptions {
+    fn default() -> Self {
+        Self {
+            null_value: String::new(),
+            line_terminator: LineTerminator::Lf,
+            drop_trailing_column: true,
+            null_check: NullLikeCheck::new(),
+        }
+    }
+}
+
+// How many cells (or rows) each rule changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FixStats {
+    pub rows: usize,
+    pub trimmed: usize,
+    pub whitespace_to_null: usize,
+    pub null_like_to_null: usize,
+    pub dropped_cells: usize,
+    pub dropped_non_empty: usize, // values lost with the trailing column
+    pub line_endings: usize,      // rows written with a different line ending
+}
+
+impl FixStats {
+    pub fn merge(&mut self, other: &FixStats) {
+        self.rows += other.rows;
+        self.trimmed += other.trimmed;
+        self.whitespace_to_null += other.whitespace_to_null;
+        self.null_like_to_null += other.null_like_to_null;
+        self.dropped_cells += other.dropped_cells;
+        self.dropped_non_empty += other.dropped_non_empty;
+        self.line_endings += other.line_endings;
+    }
+}
+
+// Warehouse exports end every line with a delimiter, which shows up as one more
+// column without a name
+pub fn has_trailing_unnamed_column(headers: &[String]) -> bool {
+    headers.len() > 1 && headers.last().is_some_and(|h| h.trim().is_empty())
+}
+
+// Applies the fix rules to single records, shared by all worker threads
+pub struct RecordFixer {
+    options: FixOptions,
+    drop_column: Option<usize>,
+    convert_line_endings: bool,
+}
+
+impl RecordFixer {
+    pub fn new(options: FixOptions, headers: &[String], source_terminator: LineTerminator) -> Self {
+        let drop_column = (options.drop_trailing_column && has_trailing_unnamed_column(headers))
+            .then(|| headers.len() - 1);
+        Self {
+            convert_line_endings: source_terminator != options.line_terminator,
+            options,
+            drop_column,
+        }
+    }
+
+    pub fn dropped_column(&self) -> Option<usize> {
+        self.drop_column
+    }
+
+    // Header row as it will be written
+    pub fn fix_headers(&self, headers: &[String]) -> csv::StringRecord {
+        headers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != self.drop_column)
+            .map(|(_, header)| header.trim())
+            .collect()
+    }
+
+    pub fn fix_record(
+        &self,
+        record: &csv::StringRecord,
+        stats: &mut FixStats,
+    ) -> csv::StringRecord {
+        stats.rows += 1;
+        if self.convert_line_endings {
+            stats.line_endings += 1;
+        }
+
+        let mut fixed = csv::StringRecord::with_capacity(record.as_slice().len(), record.len());
+        for (i, value) in record.iter().enumerate() {
+            if Some(i) == self.drop_column {
+                stats.dropped_cells += 1;
+                if !value.is_empty() {
+                    stats.dropped_non_empty += 1;
+                }
+                continue;
+            }
+
+            let trimmed = value.trim();
+            if trimmed.is_empty() && !value.is_empty() {
+                stats.whitespace_to_null += 1;
+                fixed.push_field(&self.options.null_value);
+            } else if self.options.null_check.check_column(i, value)
+                && value != self.options.null_value
+            {
+                stats.null_like_to_null += 1;
+                fixed.push_field(&self.options.null_value);
+            } else {
+                if trimmed.len() != value.len() {
+                    stats.trimmed += 1;
+                }
+                fixed.push_field(trimmed);
+            }
+        }
+        fixed
+    }
+
+    // Fix a chunk, in parallel when enabled, keeping the row order
+    pub fn fix_chunk(
+        &self,
+        records: &[csv::StringRecord],
+        enable_parallel: bool,
+    ) -> (Vec<csv::StringRecord>, FixStats) {
+        if !enable_parallel {
+            let mut stats = FixStats::default();
+            let fixed = records
+                .iter()
+                .map(|record| self.fix_record(record, &mut stats))
+                .collect();
+            return (fixed, stats);
+        }
+
+        let (fixed, stats): (Vec<_>, Vec<_>) = records
+            .par_iter()
+            .map(|record| {
+                let mut stats = FixStats::default();
+                (self.fix_record(record, &mut stats), stats)
+            })
+            .unzip();
+        let total = stats.iter().fold(FixStats::default(), |mut total, s| {
+            total.merge(s);
+            total
+        });
+        (fixed, total)
+    }
+}
+
+// Read the input chunk by chunk and write the fixed rows, the header row is written
+// first when there is one
+pub fn fix_csv<R: Read, W: Write>(
+    chunk_iterator: CsvChunkIterator<'_, R>,
+    fixer: &RecordFixer,
+    headers: Option<&[String]>,
+    writer: &mut csv::Writer<W>,
+    enable_parallel: bool,
+) -> Result<FixStats, Box<dyn Error>> {
+    let mut stats = FixStats::default();
+    if let Some(headers) = headers {
+        writer.write_record(&fixer.fix_headers(headers))?;
+    }
+
+    for chunk in chunk_iterator {
+        let (fixed, chunk_stats) = fixer.fix_chunk(&chunk?, enable_parallel);
+        for record in &fixed {
+            writer.write_record(record)?;
+        }
+        stats.merge(&chunk_stats);
+    }
+
+    writer.flush()?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(data: &str, options: FixOptions, parallel: bool) -> (String, FixStats) {
+        let mut rdr = csv::Reader::from_reader(data.as_bytes());
+        let headers: Vec<String> = rdr.headers().unwrap().iter().map(String::from).collect();
+        let fixer = RecordFixer::new(options.clone(), &headers, LineTerminator::CrLf);
+
+        let mut writer = csv::WriterBuilder::new()
+            .terminator(csv::Terminator::Any(b'\n'))
+            .from_writer(Vec::new());
+        let chunks = CsvChunkIterator::new(rdr.records(), 2);
+        let stats = fix_csv(chunks, &fixer, Some(&headers), &mut writer, parallel).unwrap();
+        (
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            stats,
+        )
+    }
+
+    #[test]
+    fn test_fix_rules() {
+        let data = "id,name,email,\r\n1, Ann ,NULL,\r\n2,   ,n/a,\r\n3,Bob,b@c.com,x\r\n";
+        for parallel in [false, true] {
+            let (output, stats) = fix(data, FixOptions::default(), parallel);
+            assert_eq!(output, "id,name,email\n1,Ann,\n2,,\n3,Bob,b@c.com\n");
+            assert_eq!(
+                stats,
+                FixStats {
+                    rows: 3,
+                    trimmed: 1,
+                    whitespace_to_null: 1,
+                    null_like_to_null: 2,
+                    dropped_cells: 3,
+                    dropped_non_empty: 1,
+                    line_endings: 3,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_backslash_n_null_and_kept_column() {
+        let options = FixOptions {
+            null_value: "\\N".to_string(),
+            drop_trailing_column: false,
+            ..FixOptions::default()
+        };
+        let (output, stats) = fix("a,\n NA ,\n", options, false);
+        assert_eq!(output, "a,\n\\N,\n");
+        assert_eq!(stats.null_like_to_null, 1);
+        assert_eq!(stats.dropped_cells, 0);
+    }
+
+    #[test]
+    fn test_trailing_unnamed_column() {
+        let headers = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(has_trailing_unnamed_column(&headers(&["a", "b", ""])));
+        assert!(!has_trailing_unnamed_column(&headers(&["a", "b"])));
+        assert!(!has_trailing_unnamed_column(&headers(&[""])));
+    }
+}
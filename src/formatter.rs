@@ -1,5 +1,13 @@
-use crate::ChunkProcessingResult;
-use std::collections::HashMap;
+use crate::{
+    CheckRegistry, ChunkProcessingResult, CsvAggregator, DuplicateReport, FixStats,
+    InvalidUtf8Stats, PatternCheck, RowShapeStats, RuleResult, ThresholdViolation,
+};
+
+// Ragged rows listed in the table output, the JSON report has all kept rows
+const RAGGED_ROWS_SHOWN: usize = 20;
+
+// Same for cells that aren't valid UTF-8
+const INVALID_CELLS_SHOWN: usize = 20;
 
 // Spark-style table formatter
 pub struct SparkStyleFormatter {
@@ -26,58 +34,64 @@ impl SparkStyleFormatter {
         self
     }
 
-    // Format chunk results in Spark style
-    pub fn format_chunk_results(
-        &self,
-        results: &[ChunkProcessingResult],
-        headers: &[String],
-    ) -> String {
+    // Format the aggregated results in Spark style
+    pub fn format_report(&self, aggregator: &CsvAggregator) -> String {
         let mut output = String::new();
+        let registry = aggregator.registry();
 
         // Create summary table
-        output.push_str(&self.format_summary_table(results, headers));
+        output.push_str(&self.format_summary_table(aggregator));
         output.push('\n');
 
+        // Rows with the wrong number of fields, only present in tolerant mode
+        let row_shape = aggregator.row_shape();
+        if row_shape.ragged_rows() > 0 {
+            output.push_str(&self.format_row_structure(row_shape));
+            output.push('\n');
+        }
+
+        // Cells that aren't valid UTF-8, only present with --byte-records
+        let invalid_utf8 = aggregator.invalid_utf8();
+        if invalid_utf8.invalid_cells() > 0 {
+            output.push_str(&self.format_invalid_utf8(invalid_utf8, aggregator.headers()));
+            output.push('\n');
+        }
+
         // Comprehensive table showing all checks for all columns
-        output.push_str(&self.format_comprehensive_table(results, headers));
+        output.push_str(&self.format_comprehensive_table(aggregator));
         output.push('\n');
 
-        // Create detailed table for each issue type
-        output
-            .push_str(&self.format_issues_table(results, headers, "NULL-like", |r| &r.null_counts));
-        output.push('\n');
-        output.push_str(&self.format_issues_table(results, headers, "Empty", |r| &r.empty_counts));
+        // Distribution stats for the numeric columns
+        output.push_str(&self.format_numeric_profile_table(aggregator));
         output.push('\n');
-        output.push_str(
-            &self.format_issues_table(results, headers, "Whitespace", |r| &r.whitespace_counts),
-        );
+
+        // Approximate distinct counts and most frequent values
+        output.push_str(&self.format_cardinality_table(aggregator));
+
+        // Formats and problems of the date and timestamp columns
+        let date_table = self.format_date_table(aggregator);
+        if !date_table.is_empty() {
+            output.push('\n');
+            output.push_str(&date_table);
+        }
+
+        // Create detailed table for each issue type
+        for check in registry.general_checks() {
+            output.push('\n');
+            output.push_str(&self.format_issues_table(aggregator, check.as_ref()));
+        }
 
         output
     }
 
     // Format summary table
-    fn format_summary_table(
-        &self,
-        results: &[ChunkProcessingResult],
-        headers: &[String],
-    ) -> String {
+    fn format_summary_table(&self, aggregator: &CsvAggregator) -> String {
         let mut output = String::new();
+        let headers = aggregator.headers();
 
         // Calculate totals
-        let total_rows: usize = results.iter().map(|r| r.rows_processed).sum();
-        let total_chunks = results.len();
-        let total_nulls: usize = results
-            .iter()
-            .map(|r| r.null_counts.values().sum::<usize>())
-            .sum();
-        let total_empty: usize = results
-            .iter()
-            .map(|r| r.empty_counts.values().sum::<usize>())
-            .sum();
-        let total_whitespace: usize = results
-            .iter()
-            .map(|r| r.whitespace_counts.values().sum::<usize>())
-            .sum();
+        let total_rows = aggregator.total_rows();
+        let total_chunks = aggregator.total_chunks();
 
         let total_cells = total_rows * headers.len();
 
@@ -87,7 +101,7 @@ impl SparkStyleFormatter {
             "Count".to_string(),
             "% of All Cells".to_string(),
         ];
-        let rows = vec![
+        let mut rows = vec![
             vec![
                 "Total Rows".to_string(),
                 total_rows.to_string(),
@@ -103,43 +117,23 @@ impl SparkStyleFormatter {
                 total_cells.to_string(),
                 "100.000%".to_string(),
             ],
-            vec![
-                "NULL-like Values".to_string(),
-                total_nulls.to_string(),
-                format!(
-                    "{:.3}%",
-                    if total_cells > 0 {
-                        (total_nulls as f64 / total_cells as f64) * 100.0
-                    } else {
-                        0.0
-                    }
-                ),
-            ],
-            vec![
-                "Empty Values".to_string(),
-                total_empty.to_string(),
-                format!(
-                    "{:.3}%",
-                    if total_cells > 0 {
-                        (total_empty as f64 / total_cells as f64) * 100.0
-                    } else {
-                        0.0
-                    }
-                ),
-            ],
-            vec![
-                "Whitespace Values".to_string(),
-                total_whitespace.to_string(),
+        ];
+
+        for check in aggregator.registry().general_checks() {
+            let total = aggregator.total_for(check.name());
+            rows.push(vec![
+                format!("{} Values", check.label()),
+                total.to_string(),
                 format!(
                     "{:.3}%",
                     if total_cells > 0 {
-                        (total_whitespace as f64 / total_cells as f64) * 100.0
+                        (total as f64 / total_cells as f64) * 100.0
                     } else {
                         0.0
                     }
                 ),
-            ],
-        ];
+            ]);
+        }
 
         output.push_str("=== PROCESSING SUMMARY ===\n");
         output.push_str(&self.format_table_owned(&table_headers, &rows));
@@ -154,112 +148,278 @@ impl SparkStyleFormatter {
     }
 
     // Comprehensive table showing all checks for all columns
-    fn format_comprehensive_table(
-        &self,
-        results: &[ChunkProcessingResult],
-        headers: &[String],
-    ) -> String {
+    fn format_comprehensive_table(&self, aggregator: &CsvAggregator) -> String {
         let mut output = String::new();
+        let headers = aggregator.headers();
+        let registry = aggregator.registry();
+        let total_rows = aggregator.total_rows();
 
-        // Aggregate counts across all chunks for each issue type
-        let mut total_null_counts = HashMap::new();
-        let mut total_empty_counts = HashMap::new();
-        let mut total_whitespace_counts = HashMap::new();
+        // Create comprehensive table headers
+        let mut table_headers = vec!["Column".to_string(), "Column Name".to_string()];
+        for check in registry.general_checks() {
+            table_headers.push(format!("{} Count", check.label()));
+            table_headers.push(format!("{} % of Column", check.label()));
+        }
+        table_headers.push("Inferred Type".to_string());
+        table_headers.push("Type Mismatch Count".to_string());
+        table_headers.push("Type Mismatch % of Column".to_string());
 
-        for result in results {
-            for (col, count) in &result.null_counts {
-                *total_null_counts.entry(*col).or_insert(0) += count;
-            }
-            for (col, count) in &result.empty_counts {
-                *total_empty_counts.entry(*col).or_insert(0) += count;
+        let mut rows = Vec::new();
+
+        // Show ALL columns
+        for (col_idx, header) in headers.iter().enumerate() {
+            let column_name = self.truncate_string(header);
+            let mut row = vec![col_idx.to_string(), column_name];
+
+            for check in registry.general_checks() {
+                let count = aggregator.count_for(check.name(), col_idx);
+
+                // Calculate percentage of this column's cells (not all rows)
+                let percentage = if total_rows > 0 {
+                    (count as f64 / total_rows as f64) * 100.0
+                } else {
+                    0.0
+                };
+
+                row.push(count.to_string());
+                row.push(format!("{:.1}%", percentage));
             }
-            for (col, count) in &result.whitespace_counts {
-                *total_whitespace_counts.entry(*col).or_insert(0) += count;
+
+            // Type inference columns, "-" when the column only had missing values
+            let types = &aggregator.column_stats()[col_idx].profile().types;
+            match types.inferred() {
+                Some(inferred) => {
+                    let mismatches = types.mismatch_count();
+                    let percentage = if total_rows > 0 {
+                        (mismatches as f64 / total_rows as f64) * 100.0
+                    } else {
+                        0.0
+                    };
+                    row.push(inferred.as_str().to_string());
+                    row.push(mismatches.to_string());
+                    row.push(format!("{:.1}%", percentage));
+                }
+                None => {
+                    row.push("-".to_string());
+                    row.push("-".to_string());
+                    row.push("-".to_string());
+                }
             }
+
+            rows.push(row);
         }
 
-        let total_rows: usize = results.iter().map(|r| r.rows_processed).sum();
+        output.push_str("=== DATA QUALITY SUMMARY BY COLUMN ===\n");
+        output.push_str(&self.format_table_owned(&table_headers, &rows));
+
+        output
+    }
+
+    // Numeric profile for every column inferred as integer or decimal
+    fn format_numeric_profile_table(&self, aggregator: &CsvAggregator) -> String {
+        let mut output = String::new();
+        let headers = aggregator.headers();
+        let stats = aggregator.column_stats();
 
-        // Create comprehensive table headers
         let table_headers = vec![
             "Column".to_string(),
             "Column Name".to_string(),
-            "NULL Count".to_string(),
-            "NULL % of Column".to_string(),
-            "Empty Count".to_string(),
-            "Empty % of Column".to_string(),
-            "Whitespace Count".to_string(),
-            "Whitespace % of Column".to_string(),
+            "Count".to_string(),
+            "Min".to_string(),
+            "Max".to_string(),
+            "Mean".to_string(),
+            "Std Dev".to_string(),
+            "P25".to_string(),
+            "Median".to_string(),
+            "P75".to_string(),
+            "P95".to_string(),
         ];
 
-        let mut rows = Vec::new();
+        let rows: Vec<Vec<String>> = headers
+            .iter()
+            .enumerate()
+            .filter_map(|(col_idx, header)| {
+                let profile = Some(stats[col_idx].profile()).filter(|p| p.is_numeric())?;
+                let stats = &profile.numeric;
+                Some(vec![
+                    col_idx.to_string(),
+                    self.truncate_string(header),
+                    stats.count().to_string(),
+                    format_number(stats.min()),
+                    format_number(stats.max()),
+                    format_number(stats.mean()),
+                    format_number(stats.stddev()),
+                    format_number(stats.quantile(0.25)),
+                    format_number(stats.quantile(0.5)),
+                    format_number(stats.quantile(0.75)),
+                    format_number(stats.quantile(0.95)),
+                ])
+            })
+            .collect();
+
+        output.push_str("=== NUMERIC PROFILE ===\n");
+        if rows.is_empty() {
+            output.push_str("No numeric columns found\n");
+        } else {
+            output.push_str(&self.format_table_owned(&table_headers, &rows));
+            output.push_str("Quantiles are approximate (t-digest)\n");
+        }
 
-        // Show ALL columns
+        output
+    }
+
+    // Cardinality profile for every column
+    fn format_cardinality_table(&self, aggregator: &CsvAggregator) -> String {
+        let mut output = String::new();
+        let headers = aggregator.headers();
+        let stats = aggregator.column_stats();
+
+        let table_headers = vec![
+            "Column".to_string(),
+            "Column Name".to_string(),
+            "Non-Missing".to_string(),
+            "Approx Distinct".to_string(),
+            "Distinct % Non-Missing".to_string(),
+            "Top Value".to_string(),
+            "Top Value Count".to_string(),
+            "Top Value % Non-Missing".to_string(),
+        ];
+
+        let mut rows = Vec::new();
         for (col_idx, header) in headers.iter().enumerate() {
-            let column_name = self.truncate_string(header);
+            let mut row = vec![col_idx.to_string(), self.truncate_string(header)];
+
+            match Some(stats[col_idx].profile()).filter(|p| p.types.non_missing() > 0) {
+                Some(profile) => {
+                    let non_missing = profile.types.non_missing();
+                    // HLL can overshoot slightly, never report more distinct values than values
+                    let distinct = profile.distinct.estimate().min(non_missing);
+                    row.push(non_missing.to_string());
+                    row.push(distinct.to_string());
+                    row.push(format!(
+                        "{:.1}%",
+                        (distinct as f64 / non_missing as f64) * 100.0
+                    ));
+
+                    match profile.top_values.top(1).first() {
+                        Some((value, count)) => {
+                            row.push(self.truncate_string(&format!("'{}'", value)));
+                            row.push(count.to_string());
+                            row.push(format!(
+                                "{:.1}%",
+                                (*count as f64 / non_missing as f64) * 100.0
+                            ));
+                        }
+                        None => row.extend(["-".to_string(), "-".to_string(), "-".to_string()]),
+                    }
+                }
+                None => row.extend(std::iter::repeat_n("-".to_string(), 6)),
+            }
 
-            let null_count = total_null_counts.get(&col_idx).copied().unwrap_or(0);
-            let empty_count = total_empty_counts.get(&col_idx).copied().unwrap_or(0);
-            let whitespace_count = total_whitespace_counts.get(&col_idx).copied().unwrap_or(0);
+            rows.push(row);
+        }
 
-            // Calculate percentage of this column's cells (not all rows)
-            let null_percentage = if total_rows > 0 {
-                (null_count as f64 / total_rows as f64) * 100.0
-            } else {
-                0.0
-            };
-            let empty_percentage = if total_rows > 0 {
-                (empty_count as f64 / total_rows as f64) * 100.0
-            } else {
-                0.0
-            };
-            let whitespace_percentage = if total_rows > 0 {
-                (whitespace_count as f64 / total_rows as f64) * 100.0
-            } else {
-                0.0
-            };
+        output.push_str("=== CARDINALITY PROFILE ===\n");
+        output.push_str(&self.format_table_owned(&table_headers, &rows));
+        output.push_str(
+            "Distinct counts are HyperLogLog estimates, top value counts are Misra-Gries lower bounds\n",
+        );
 
+        output
+    }
+
+    // Date formats per column, only columns with date-like values are listed
+    fn format_date_table(&self, aggregator: &CsvAggregator) -> String {
+        let mut output = String::new();
+        let headers = aggregator.headers();
+        let stats = aggregator.column_stats();
+
+        let table_headers = vec![
+            "Column".to_string(),
+            "Column Name".to_string(),
+            "Date Values".to_string(),
+            "Formats".to_string(),
+            "Impossible".to_string(),
+            "With TZ".to_string(),
+            "Without TZ".to_string(),
+            "Outside Window".to_string(),
+            "Min".to_string(),
+            "Max".to_string(),
+            "Status".to_string(),
+        ];
+
+        let mut rows = Vec::new();
+        let mut format_lines = Vec::new();
+        for (col_idx, header) in headers.iter().enumerate() {
+            let dates = &stats[col_idx].profile().dates;
+            if dates.is_empty() {
+                continue;
+            }
+
+            let formats = dates.formats();
+            let issues = dates.issues();
+            let format_date = |date: Option<chrono::NaiveDate>| {
+                date.map_or("-".to_string(), |date| date.to_string())
+            };
             rows.push(vec![
                 col_idx.to_string(),
-                column_name,
-                null_count.to_string(),
-                format!("{:.1}%", null_percentage),
-                empty_count.to_string(),
-                format!("{:.1}%", empty_percentage),
-                whitespace_count.to_string(),
-                format!("{:.1}%", whitespace_percentage),
+                self.truncate_string(header),
+                dates.total().to_string(),
+                formats.len().to_string(),
+                dates.impossible().to_string(),
+                dates.with_zone().to_string(),
+                dates.without_zone().to_string(),
+                dates.outside_window().to_string(),
+                format_date(dates.min()),
+                format_date(dates.max()),
+                if issues.is_empty() { "OK" } else { "FLAGGED" }.to_string(),
             ]);
+
+            let total = dates.total() as f64;
+            let counts: Vec<String> = formats
+                .iter()
+                .map(|(format, count)| {
+                    format!(
+                        "{} {} ({:.1}%)",
+                        format,
+                        count,
+                        *count as f64 / total * 100.0
+                    )
+                })
+                .collect();
+            let flagged = if issues.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", issues.join(", "))
+            };
+            format_lines.push(format!("{}: {}{}\n", header, counts.join(", "), flagged));
         }
 
-        output.push_str("=== DATA QUALITY SUMMARY BY COLUMN ===\n");
+        if rows.is_empty() {
+            return output;
+        }
+
+        output.push_str("=== DATE FORMATS ===\n");
         output.push_str(&self.format_table_owned(&table_headers, &rows));
+        for line in format_lines {
+            output.push_str(&line);
+        }
+        output.push_str(&format!(
+            "Plausible date window: {}\n",
+            aggregator.registry().date_window()
+        ));
 
         output
     }
 
     // Format issues table
-    fn format_issues_table<F>(
-        &self,
-        results: &[ChunkProcessingResult],
-        headers: &[String],
-        issue_type: &str,
-        extractor: F,
-    ) -> String
-    where
-        F: Fn(&ChunkProcessingResult) -> &HashMap<usize, usize>,
-    {
+    fn format_issues_table(&self, aggregator: &CsvAggregator, check: &dyn PatternCheck) -> String {
         let mut output = String::new();
+        let issue_type = check.label();
+        let headers = aggregator.headers();
 
-        // Aggregate counts across all chunks
-        let mut total_counts = HashMap::new();
-        for result in results {
-            for (col, count) in extractor(result) {
-                *total_counts.entry(*col).or_insert(0) += count;
-            }
-        }
-
-        let total_rows: usize = results.iter().map(|r| r.rows_processed).sum();
-        let total_issues: usize = total_counts.values().sum();
+        let total_rows = aggregator.total_rows();
+        let total_issues = aggregator.total_for(check.name());
 
         // Create table
         let table_headers = vec![
@@ -280,7 +440,7 @@ impl SparkStyleFormatter {
                 "Unknown Column".to_string()
             };
 
-            let count = total_counts.get(&col_idx).copied().unwrap_or(0);
+            let count = aggregator.count_for(check.name(), col_idx);
 
             // Percentage of this specific issue type (e.g., 40.5% of all NULL values)
             let percentage_of_issue_type = if total_issues > 0 && count > 0 {
@@ -329,6 +489,242 @@ impl SparkStyleFormatter {
         output
     }
 
+    // Line numbers and field counts of ragged rows
+    pub fn format_row_structure(&self, row_shape: &RowShapeStats) -> String {
+        let mut output = String::new();
+
+        let table_headers = vec![
+            "Line".to_string(),
+            "Expected Fields".to_string(),
+            "Actual Fields".to_string(),
+        ];
+
+        let rows: Vec<Vec<String>> = row_shape
+            .rows
+            .iter()
+            .take(RAGGED_ROWS_SHOWN)
+            .map(|row| {
+                vec![
+                    row.line.to_string(),
+                    row.expected_fields.to_string(),
+                    row.actual_fields.to_string(),
+                ]
+            })
+            .collect();
+
+        output.push_str("=== ROW STRUCTURE ===\n");
+        output.push_str(&self.format_table_owned(&table_headers, &rows));
+        output.push_str(&format!(
+            "Ragged rows: {} ({} short, {} long)\n",
+            row_shape.ragged_rows(),
+            row_shape.short_rows,
+            row_shape.long_rows
+        ));
+        if rows.len() < row_shape.ragged_rows() {
+            output.push_str(&format!("Showing the first {} rows\n", rows.len()));
+        }
+
+        output
+    }
+
+    // Line numbers and columns of cells that aren't valid UTF-8, then the count per column
+    pub fn format_invalid_utf8(&self, stats: &InvalidUtf8Stats, headers: &[String]) -> String {
+        let mut output = String::new();
+        let column_name = |column: usize| {
+            headers
+                .get(column)
+                .cloned()
+                .unwrap_or_else(|| format!("col_{}", column))
+        };
+
+        let table_headers = vec![
+            "Line".to_string(),
+            "Column".to_string(),
+            "Value".to_string(),
+        ];
+        let rows: Vec<Vec<String>> = stats
+            .cells
+            .iter()
+            .take(INVALID_CELLS_SHOWN)
+            .map(|cell| {
+                vec![
+                    cell.line.to_string(),
+                    column_name(cell.column),
+                    cell.value.clone(),
+                ]
+            })
+            .collect();
+
+        output.push_str("=== INVALID UTF-8 ===\n");
+        output.push_str(&self.format_table_owned(&table_headers, &rows));
+        let per_column: Vec<String> = stats
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(column, count)| format!("{}: {}", column_name(column), count))
+            .collect();
+        output.push_str(&format!(
+            "Invalid UTF-8 cells: {} ({})\n",
+            stats.invalid_cells(),
+            per_column.join(", ")
+        ));
+        if rows.len() < stats.invalid_cells() {
+            output.push_str(&format!("Showing the first {} cells\n", rows.len()));
+        }
+
+        output
+    }
+
+    // Cells changed by each rule of the `fix` subcommand
+    pub fn format_fix_summary(&self, stats: &FixStats) -> String {
+        let mut output = String::new();
+
+        let table_headers = vec!["Rule".to_string(), "Cells Changed".to_string()];
+        let rows: Vec<Vec<String>> = [
+            ("Trimmed whitespace", stats.trimmed),
+            ("Whitespace-only to null", stats.whitespace_to_null),
+            ("NULL-like to null", stats.null_like_to_null),
+            ("Trailing column dropped", stats.dropped_cells),
+            ("Line endings (rows)", stats.line_endings),
+        ]
+        .iter()
+        .map(|(rule, count)| vec![rule.to_string(), count.to_string()])
+        .collect();
+
+        output.push_str("=== FIX SUMMARY ===\n");
+        output.push_str(&self.format_table_owned(&table_headers, &rows));
+        output.push_str(&format!("Rows written: {}\n", stats.rows));
+        if stats.dropped_non_empty > 0 {
+            output.push_str(&format!(
+                "Warning: the dropped trailing column had {} non-empty values\n",
+                stats.dropped_non_empty
+            ));
+        }
+
+        output
+    }
+
+    // Pass/fail of every rule from the rules file
+    pub fn format_rule_results(&self, results: &[RuleResult]) -> String {
+        let mut output = String::new();
+
+        let table_headers = vec![
+            "Column".to_string(),
+            "Column Name".to_string(),
+            "Rule".to_string(),
+            "Expectation".to_string(),
+            "Violations".to_string(),
+            "Status".to_string(),
+        ];
+
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|result| {
+                vec![
+                    result.column_index.to_string(),
+                    self.truncate_string(&result.column),
+                    result.rule.clone(),
+                    self.truncate_string(&result.expectation),
+                    result.violations.to_string(),
+                    if result.passed { "PASS" } else { "FAIL" }.to_string(),
+                ]
+            })
+            .collect();
+
+        let failed = results.iter().filter(|result| !result.passed).count();
+        output.push_str("=== RULES ===\n");
+        output.push_str(&self.format_table_owned(&table_headers, &rows));
+        output.push_str(&format!(
+            "Rules passed: {} of {}\n",
+            results.len() - failed,
+            results.len()
+        ));
+
+        output
+    }
+
+    // Table of every threshold rule that was broken
+    pub fn format_threshold_violations(&self, violations: &[ThresholdViolation]) -> String {
+        let mut output = String::new();
+
+        let table_headers = vec![
+            "Column".to_string(),
+            "Column Name".to_string(),
+            "Check".to_string(),
+            "Count".to_string(),
+            "% of Column".to_string(),
+            "Max Allowed %".to_string(),
+        ];
+
+        let rows: Vec<Vec<String>> = violations
+            .iter()
+            .map(|violation| {
+                vec![
+                    violation.column_index.to_string(),
+                    self.truncate_string(&violation.column_name),
+                    self.truncate_string(&violation.check_name),
+                    violation.count.to_string(),
+                    format!("{:.3}%", violation.percent),
+                    format!("{:.3}%", violation.max_percent),
+                ]
+            })
+            .collect();
+
+        output.push_str("=== THRESHOLD VIOLATIONS ===\n");
+        if rows.is_empty() {
+            output.push_str("All thresholds passed\n");
+        } else {
+            output.push_str(&self.format_table_owned(&table_headers, &rows));
+            output.push_str(&format!("Total violations: {}\n", violations.len()));
+        }
+
+        output
+    }
+
+    pub fn format_duplicate_report(&self, report: &DuplicateReport) -> String {
+        let mut output = String::new();
+
+        match &report.key_columns {
+            Some(keys) => {
+                output.push_str(&format!("=== DUPLICATE KEYS ({}) ===\n", keys.join(", ")))
+            }
+            None => output.push_str("=== DUPLICATE ROWS ===\n"),
+        }
+
+        if report.duplicate_groups == 0 {
+            output.push_str("No duplicates found\n");
+            return output;
+        }
+
+        // Line number lists are too wide for the table layout, so these are listed plainly
+        for (i, group) in report.groups.iter().enumerate() {
+            let mut lines: Vec<String> = group.line_numbers.iter().map(|l| l.to_string()).collect();
+            if (group.line_numbers.len() as u64) < group.occurrences {
+                lines.push("...".to_string());
+            }
+            output.push_str(&format!(
+                "  Group {}: {} occurrences at lines {}\n",
+                i + 1,
+                group.occurrences,
+                lines.join(", ")
+            ));
+        }
+
+        output.push_str(&format!(
+            "Duplicate groups: {}, duplicate rows: {}\n",
+            report.duplicate_groups, report.duplicate_rows
+        ));
+        if (report.groups.len() as u64) < report.duplicate_groups {
+            output.push_str(&format!(
+                "Showing the first {} groups\n",
+                report.groups.len()
+            ));
+        }
+
+        output
+    }
+
     // Table formatting function that works with owned Strings
     fn format_table_owned(&self, headers: &[String], rows: &[Vec<String>]) -> String {
         if rows.is_empty() {
@@ -338,10 +734,11 @@ impl SparkStyleFormatter {
         // Calculate column widths
         let mut col_widths = Vec::new();
         for (i, header) in headers.iter().enumerate() {
-            let mut max_width = header.len();
+            // Widths count characters, padding in format! does too
+            let mut max_width = header.chars().count();
             for row in rows {
                 if i < row.len() {
-                    max_width = max_width.max(row[i].len());
+                    max_width = max_width.max(row[i].chars().count());
                 }
             }
             col_widths.push(max_width.min(self.max_col_width));
@@ -415,10 +812,11 @@ impl SparkStyleFormatter {
     }
 
     fn truncate_to_width(&self, s: &str, width: usize) -> String {
-        if s.len() <= width {
+        if s.chars().count() <= width {
             s.to_string()
         } else if self.show_truncation && width > 3 {
-            format!("{}...", &s[..width - 3])
+            // Cut on a character boundary, values can be multi-byte
+            format!("{}...", s.chars().take(width - 3).collect::<String>())
         } else {
             s.chars().take(width).collect()
         }
@@ -429,6 +827,22 @@ impl SparkStyleFormatter {
     }
 }
 
+// Up to 4 decimal places without trailing zeros, "-" when there is no value
+fn format_number(value: Option<f64>) -> String {
+    match value {
+        Some(value) => {
+            let formatted = format!("{:.4}", value);
+            let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+            if trimmed == "-0" {
+                "0".to_string()
+            } else {
+                trimmed.to_string()
+            }
+        }
+        None => "-".to_string(),
+    }
+}
+
 // Console formatter for original style output
 pub struct ConsoleFormatter;
 
@@ -443,7 +857,12 @@ impl ConsoleFormatter {
         Self
     }
 
-    pub fn print_chunk_results(&self, result: &ChunkProcessingResult, headers: &[String]) {
+    pub fn print_chunk_results(
+        &self,
+        result: &ChunkProcessingResult,
+        headers: &[String],
+        registry: &CheckRegistry,
+    ) {
         println!(
             "\nProcessed chunk #{} with {} rows",
             result.chunk_number, result.rows_processed
@@ -451,105 +870,109 @@ impl ConsoleFormatter {
 
         println!("--- Statistics for chunk {}:", result.chunk_number);
 
-        self.print_null_results(result, headers);
-        self.print_empty_results(result, headers);
-        self.print_whitespace_results(result, headers);
-    }
-
-    fn print_null_results(&self, result: &ChunkProcessingResult, headers: &[String]) {
-        if !result.null_counts.is_empty() {
-            println!("NULL-like values:");
-            for (col, count) in result.null_counts.iter().filter(|(_, &count)| count > 0) {
-                let header_name = if *col < headers.len() {
-                    &headers[*col]
-                } else {
-                    "Unkown Column"
-                };
-
-                println!(
-                    "   col_{} column_name={}: {} NULL-like values",
-                    col, header_name, count
-                );
-            }
-        } else {
-            println!("No NULL-like values found in this chunk");
+        for check in registry.general_checks() {
+            self.print_check_results(result, headers, check.as_ref());
         }
     }
 
-    fn print_empty_results(&self, result: &ChunkProcessingResult, headers: &[String]) {
-        if !result.empty_counts.is_empty() {
-            println!("Empty values:");
-            for (col, count) in result.empty_counts.iter().filter(|(_, &count)| count > 0) {
-                let header_name = if *col < headers.len() {
-                    &headers[*col]
-                } else {
-                    "Unkown Column"
-                };
-
-                println!(
-                    "   col_{} column_name={}: {} empty values",
-                    col, header_name, count
-                );
-            }
-        } else {
-            println!("No empty values found in this chunk");
-        }
-    }
-
-    fn print_whitespace_results(&self, result: &ChunkProcessingResult, headers: &[String]) {
-        if !result.whitespace_counts.is_empty() {
-            println!("White Space Only values:");
-            for (col, count) in result
-                .whitespace_counts
-                .iter()
-                .filter(|(_, &count)| count > 0)
-            {
-                let header_name = if *col < headers.len() {
-                    &headers[*col]
-                } else {
-                    "Unkown Column"
-                };
-
-                println!(
-                    "   col_{} column_name={}: {} white space only values",
-                    col, header_name, count
-                );
+    fn print_check_results(
+        &self,
+        result: &ChunkProcessingResult,
+        headers: &[String],
+        check: &dyn PatternCheck,
+    ) {
+        match result.counts_for(check.name()) {
+            Some(counts) if !counts.is_empty() => {
+                println!("{} values:", check.label());
+                for (col, count) in counts.iter().filter(|(_, &count)| count > 0) {
+                    let header_name = if *col < headers.len() {
+                        &headers[*col]
+                    } else {
+                        "Unknown Column"
+                    };
+
+                    println!(
+                        "   col_{} column_name={}: {} {} values",
+                        col,
+                        header_name,
+                        count,
+                        check.label()
+                    );
+                }
             }
-        } else {
-            println!("No white space only values found in this chunk");
+            _ => println!("No {} values found in this chunk", check.label()),
         }
     }
 }
 
 // Public convenience functions
-pub fn print_chunk_results_spark_style(results: &[ChunkProcessingResult], headers: &[String]) {
+pub fn print_report_spark_style(aggregator: &CsvAggregator) {
     let formatter = SparkStyleFormatter::new().with_max_width(25);
-    let formatted_output = formatter.format_chunk_results(results, headers);
+    let formatted_output = formatter.format_report(aggregator);
     println!("{}", formatted_output);
 }
 
-pub fn print_chunk_results_console_style(result: &ChunkProcessingResult, headers: &[String]) {
+pub fn print_chunk_results_console_style(
+    result: &ChunkProcessingResult,
+    headers: &[String],
+    registry: &CheckRegistry,
+) {
     let formatter = ConsoleFormatter::new();
-    formatter.print_chunk_results(result, headers);
+    formatter.print_chunk_results(result, headers, registry);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ColumnProfile, EmptyCheck, NullLikeCheck, WhiteSpaceOnlyCheck};
+    use std::collections::HashMap;
+
+    // Aggregate of a single chunk
+    fn aggregate(
+        result: &ChunkProcessingResult,
+        headers: &[String],
+        registry: &CheckRegistry,
+    ) -> CsvAggregator {
+        let mut aggregator = CsvAggregator::new(headers.to_vec(), 1000, registry);
+        aggregator.add_chunk_results(result);
+        aggregator
+    }
+
+    fn chunk_result(
+        rows_processed: usize,
+        null_counts: HashMap<usize, usize>,
+        empty_counts: HashMap<usize, usize>,
+        whitespace_counts: HashMap<usize, usize>,
+    ) -> ChunkProcessingResult {
+        ChunkProcessingResult {
+            chunk_number: 1,
+            rows_processed,
+            check_counts: [
+                (NullLikeCheck::NAME.to_string(), null_counts),
+                (EmptyCheck::NAME.to_string(), empty_counts),
+                (WhiteSpaceOnlyCheck::NAME.to_string(), whitespace_counts),
+            ]
+            .into_iter()
+            .collect(),
+            column_profiles: Vec::new(),
+            row_shape: RowShapeStats::default(),
+            invalid_utf8: InvalidUtf8Stats::default(),
+            findings: Vec::new(),
+        }
+    }
 
     #[test]
     fn test_percentage_calculations() {
-        let results = vec![ChunkProcessingResult {
-            chunk_number: 1,
-            rows_processed: 100,
-            null_counts: [(0, 5), (1, 15)].into_iter().collect(), // 20 total nulls
-            empty_counts: [(0, 10), (1, 30), (2, 60)].into_iter().collect(), // 100 total empty
-            whitespace_counts: [(0, 2)].into_iter().collect(),    // 2 total whitespace
-        }];
+        let result = chunk_result(
+            100,
+            [(0, 5), (1, 15)].into_iter().collect(), // 20 total nulls
+            [(0, 10), (1, 30), (2, 60)].into_iter().collect(), // 100 total empty
+            [(0, 2)].into_iter().collect(),          // 2 total whitespace
+        );
 
         let headers = vec!["col1".to_string(), "col2".to_string(), "col3".to_string()];
         let formatter = SparkStyleFormatter::new();
-        let output = formatter.format_chunk_results(&results, &headers);
+        let output = formatter.format_report(&aggregate(&result, &headers, &CheckRegistry::new()));
 
         // With 100 rows × 3 columns = 300 total cells:
         // - 20 nulls / 300 cells = 6.667%
@@ -571,23 +994,22 @@ mod tests {
     #[test]
     fn test_empty_values_dont_exceed_100_percent() {
         // Simulate your real data scenario
-        let results = vec![ChunkProcessingResult {
-            chunk_number: 1,
-            rows_processed: 1000,
-            null_counts: HashMap::new(),
-            empty_counts: [
+        let result = chunk_result(
+            1000,
+            HashMap::new(),
+            [
                 (0, 900), // 90% of column 0 is empty
                 (1, 800), // 80% of column 1 is empty
                 (2, 700), // 70% of column 2 is empty
             ]
             .into_iter()
             .collect(), // 2400 total empty values
-            whitespace_counts: HashMap::new(),
-        }];
+            HashMap::new(),
+        );
 
         let headers = vec!["col1".to_string(), "col2".to_string(), "col3".to_string()];
         let formatter = SparkStyleFormatter::new();
-        let output = formatter.format_chunk_results(&results, &headers);
+        let output = formatter.format_report(&aggregate(&result, &headers, &CheckRegistry::new()));
 
         // With 1000 rows × 3 columns = 3000 total cells:
         // 2400 empty / 3000 cells = 80.0%
@@ -604,4 +1026,115 @@ mod tests {
 
         println!("Fixed output:\n{}", output);
     }
+
+    #[test]
+    fn test_inferred_type_columns() {
+        use crate::profile::CellObservation;
+
+        let mut quantity = ColumnProfile::default();
+        for value in ["5", "2", "1", "3"] {
+            quantity.observe(&CellObservation::new(value, false));
+        }
+        quantity.observe(&CellObservation::new("hello", false));
+
+        let mut result = chunk_result(5, HashMap::new(), HashMap::new(), HashMap::new());
+        result.column_profiles = vec![quantity, ColumnProfile::default()];
+
+        let headers = vec!["quantity".to_string(), "blank".to_string()];
+        let output = SparkStyleFormatter::new().format_report(&aggregate(
+            &result,
+            &headers,
+            &CheckRegistry::new(),
+        ));
+
+        assert!(output.contains("Inferred Type"));
+        assert!(output.contains("integer"));
+        assert!(output.contains("20.0%")); // 1 of 5 rows doesn't match
+
+        // quantity is numeric so it gets a numeric profile row
+        assert!(output.contains("=== NUMERIC PROFILE ==="));
+        assert!(output.contains("| 2.75 ")); // mean of 5, 2, 1, 3
+
+        // 5 distinct values out of 5, the blank column has no profile
+        assert!(output.contains("=== CARDINALITY PROFILE ==="));
+        assert!(output.contains("100.0%"));
+    }
+
+    #[test]
+    fn test_date_format_table() {
+        use crate::profile::CellObservation;
+
+        let mut order_date = ColumnProfile::default();
+        for value in ["1/15/2024", "2024-01-15", "15.01.2024", "2/30/2024"] {
+            order_date.observe(&CellObservation::new(value, false));
+        }
+
+        let mut result = chunk_result(4, HashMap::new(), HashMap::new(), HashMap::new());
+        result.column_profiles = vec![order_date, ColumnProfile::default()];
+
+        let headers = vec!["order_date".to_string(), "blank".to_string()];
+        let output = SparkStyleFormatter::new().format_report(&aggregate(
+            &result,
+            &headers,
+            &CheckRegistry::new(),
+        ));
+
+        assert!(output.contains("=== DATE FORMATS ==="));
+        assert!(output.contains("FLAGGED"));
+        assert!(output.contains("order_date: M/D/YYYY 2 (50.0%)"));
+        assert!(output.contains("[mixed formats, impossible dates]"));
+        assert!(!output.contains("blank:"));
+    }
+
+    #[test]
+    fn test_invalid_utf8_table() {
+        let data = b"name,city\ncaf\xe9,Paris\nok,S\xe3o Paulo\n";
+        let mut rdr = csv::Reader::from_reader(&data[..]);
+        let records: Vec<csv::ByteRecord> = rdr.byte_records().map(|r| r.unwrap()).collect();
+        let (_records, stats) = crate::utf8::decode_records(records);
+
+        let mut result = chunk_result(2, HashMap::new(), HashMap::new(), HashMap::new());
+        result.invalid_utf8 = stats;
+        let headers = vec!["name".to_string(), "city".to_string()];
+        let output = SparkStyleFormatter::new().format_report(&aggregate(
+            &result,
+            &headers,
+            &CheckRegistry::new(),
+        ));
+
+        assert!(output.contains("=== INVALID UTF-8 ==="));
+        assert!(output.contains("caf\u{FFFD}"));
+        assert!(output.contains("Invalid UTF-8 cells: 2 (name: 1, city: 1)"));
+    }
+
+    #[test]
+    fn test_multibyte_values_are_truncated_by_character() {
+        use crate::profile::CellObservation;
+
+        // 24 characters but 72 bytes, a byte index would cut into a `€`
+        let long = "€".repeat(24);
+        let mut price = ColumnProfile::default();
+        for _ in 0..2 {
+            price.observe(&CellObservation::new(&long, false));
+        }
+        let mut result = chunk_result(2, HashMap::new(), HashMap::new(), HashMap::new());
+        result.column_profiles = vec![price];
+
+        let headers = vec!["price".to_string()];
+        let output = SparkStyleFormatter::new()
+            .with_max_width(20)
+            .format_report(&aggregate(&result, &headers, &CheckRegistry::new()));
+
+        // Cut to 17 characters plus "...", padded to the same 20 character column
+        assert!(output.contains(&format!("| '{}... |", "€".repeat(16))));
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(Some(29.99)), "29.99");
+        assert_eq!(format_number(Some(5.0)), "5");
+        assert_eq!(format_number(Some(1.0 / 3.0)), "0.3333");
+        assert_eq!(format_number(Some(-0.00001)), "0");
+        assert_eq!(format_number(None), "-");
+    }
 }
@@ -0,0 +1,256 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+// Date layouts we recognise, month first is tried before day first
+const DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y", "%d.%m.%Y", "%Y/%m/%d"];
+
+// Timestamp layouts without an offset, RFC 3339 is tried first
+const DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+const BOOLEAN_VALUES: [&str; 6] = ["true", "false", "yes", "no", "t", "f"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InferredType {
+    Integer,
+    Decimal,
+    Boolean,
+    Date,
+    DateTime,
+    String,
+}
+
+impl InferredType {
+    pub const ALL: [InferredType; 6] = [
+        InferredType::Integer,
+        InferredType::Decimal,
+        InferredType::Boolean,
+        InferredType::Date,
+        InferredType::DateTime,
+        InferredType::String,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InferredType::Integer => "integer",
+            InferredType::Decimal => "decimal",
+            InferredType::Boolean => "boolean",
+            InferredType::Date => "date",
+            InferredType::DateTime => "datetime",
+            InferredType::String => "string",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    // Whether a value of this type is acceptable in a column of the inferred type
+    pub fn conforms_to(&self, inferred: InferredType) -> bool {
+        *self == inferred
+            || inferred == InferredType::String
+            || (*self == InferredType::Integer && inferred == InferredType::Decimal)
+    }
+}
+
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_decimal(value: &str) -> bool {
+    // Only plain numbers, f64 parsing would also accept "NaN" and "inf"
+    let body = value.strip_prefix(['+', '-']).unwrap_or(value);
+    body.bytes().any(|b| b.is_ascii_digit())
+        && body
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-'))
+        && body.parse::<f64>().is_ok()
+}
+
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+pub fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.naive_utc());
+    }
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+}
+
+// Most specific type for a single non-missing value
+pub fn classify_value(value: &str) -> InferredType {
+    let value = value.trim();
+
+    if is_integer(value) {
+        return InferredType::Integer;
+    }
+    if is_decimal(value) {
+        return InferredType::Decimal;
+    }
+    if BOOLEAN_VALUES
+        .iter()
+        .any(|boolean| value.eq_ignore_ascii_case(boolean))
+    {
+        return InferredType::Boolean;
+    }
+
+    // Cheap shape check before handing off to chrono
+    if value.len() >= 6 && value.as_bytes()[0].is_ascii_digit() {
+        if value.len() <= 10 && parse_date(value).is_some() {
+            return InferredType::Date;
+        }
+        if parse_datetime(value).is_some() {
+            return InferredType::DateTime;
+        }
+    }
+
+    InferredType::String
+}
+
+// Mergeable per-column tally of value types
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypeCounts {
+    counts: [u64; 6],
+    missing: u64, // blank or NULL-like values, these don't vote
+}
+
+impl TypeCounts {
+    pub fn observe(&mut self, value_type: InferredType) {
+        self.counts[value_type.index()] += 1;
+    }
+
+    pub fn observe_missing(&mut self) {
+        self.missing += 1;
+    }
+
+    pub fn merge(&mut self, other: &TypeCounts) {
+        for (count, other_count) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other_count;
+        }
+        self.missing += other.missing;
+    }
+
+    pub fn count(&self, value_type: InferredType) -> u64 {
+        self.counts[value_type.index()]
+    }
+
+    pub fn missing(&self) -> u64 {
+        self.missing
+    }
+
+    pub fn non_missing(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    // Majority vote over the non-missing values. A column of integers with
+    // some decimals is widened to decimal since integers conform to it.
+    pub fn inferred(&self) -> Option<InferredType> {
+        let winner = InferredType::ALL
+            .iter()
+            .copied()
+            .filter(|value_type| self.count(*value_type) > 0)
+            .max_by(|a, b| {
+                // Earlier variants win ties
+                self.count(*a)
+                    .cmp(&self.count(*b))
+                    .then(b.index().cmp(&a.index()))
+            })?;
+
+        if winner == InferredType::Integer && self.count(InferredType::Decimal) > 0 {
+            Some(InferredType::Decimal)
+        } else {
+            Some(winner)
+        }
+    }
+
+    // Number of non-missing values that don't match the inferred type
+    pub fn mismatch_count(&self) -> u64 {
+        match self.inferred() {
+            Some(inferred) => InferredType::ALL
+                .iter()
+                .filter(|value_type| !value_type.conforms_to(inferred))
+                .map(|value_type| self.count(*value_type))
+                .sum(),
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_value() {
+        assert_eq!(classify_value("42"), InferredType::Integer);
+        assert_eq!(classify_value("-7"), InferredType::Integer);
+        assert_eq!(classify_value("29.99"), InferredType::Decimal);
+        assert_eq!(classify_value("1e5"), InferredType::Decimal);
+        assert_eq!(classify_value("NaN"), InferredType::String);
+        assert_eq!(classify_value("TRUE"), InferredType::Boolean);
+        assert_eq!(classify_value("1/15/2024"), InferredType::Date);
+        assert_eq!(classify_value("2024-01-15"), InferredType::Date);
+        assert_eq!(classify_value("15.01.2024"), InferredType::Date);
+        assert_eq!(classify_value("2024-02-30"), InferredType::String);
+        assert_eq!(
+            classify_value("2024-01-15T08:30:00Z"),
+            InferredType::DateTime
+        );
+        assert_eq!(
+            classify_value("2024-01-15 08:30:00"),
+            InferredType::DateTime
+        );
+        assert_eq!(classify_value("SKU123"), InferredType::String);
+    }
+
+    #[test]
+    fn test_majority_vote_and_mismatches() {
+        let mut counts = TypeCounts::default();
+        for _ in 0..998 {
+            counts.observe(InferredType::Integer);
+        }
+        counts.observe(InferredType::String);
+        counts.observe_missing();
+
+        assert_eq!(counts.inferred(), Some(InferredType::Integer));
+        assert_eq!(counts.mismatch_count(), 1);
+        assert_eq!(counts.missing(), 1);
+
+        // Integers conform to decimal so the column is widened
+        let mut prices = TypeCounts::default();
+        prices.observe(InferredType::Integer);
+        prices.observe(InferredType::Integer);
+        prices.observe(InferredType::Decimal);
+        assert_eq!(prices.inferred(), Some(InferredType::Decimal));
+        assert_eq!(prices.mismatch_count(), 0);
+
+        assert_eq!(TypeCounts::default().inferred(), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = TypeCounts::default();
+        a.observe(InferredType::Date);
+        let mut b = TypeCounts::default();
+        b.observe(InferredType::Date);
+        b.observe(InferredType::String);
+        b.observe_missing();
+
+        a.merge(&b);
+        assert_eq!(a.count(InferredType::Date), 2);
+        assert_eq!(a.non_missing(), 3);
+        assert_eq!(a.missing(), 1);
+        assert_eq!(a.mismatch_count(), 1);
+    }
+}
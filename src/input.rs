@@ -0,0 +1,333 @@
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+// Anything the csv reader can be built on, decompressed on the fly
+pub type InputReader = Box<dyn Read + Send>;
+
+// Extensions accepted for the data itself, before any compression suffix
+pub const DATA_EXTENSIONS: [&str; 4] = ["csv", "tsv", "psv", "txt"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        }
+    }
+
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            "xz" => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    // Magic bytes win, the extension is only a fallback for files too short to tell
+    pub fn detect(path: &Path) -> io::Result<Self> {
+        let mut magic = Vec::with_capacity(6);
+        File::open(path)?.take(6).read_to_end(&mut magic)?;
+
+        Ok(Compression::from_magic(&magic)
+            .or_else(|| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(Compression::from_extension)
+            })
+            .unwrap_or(Compression::None))
+    }
+
+    // Wrap a reader in the matching streaming decoder
+    pub fn decoder<R: Read + Send + 'static>(self, reader: R) -> io::Result<InputReader> {
+        let reader = BufReader::new(reader);
+        Ok(match self {
+            Compression::None => Box::new(reader),
+            // Multi-member/multi-stream decoders, concatenated archives are common for exports
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(reader)?),
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+            Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+        })
+    }
+}
+
+// Open a file and decompress it transparently
+pub fn open_input(path: &Path) -> io::Result<(Compression, InputReader)> {
+    let compression = Compression::detect(path)?;
+    let reader = compression.decoder(File::open(path)?)?;
+    Ok((compression, reader))
+}
+
+// Where a dataset is read from, `-` on the command line means stdin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Stdin,
+    File(PathBuf),
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Stdin => write!(f, "<stdin>"),
+            InputSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl InputSource {
+    // Stdin can't be reopened, so the magic bytes are read once and put back in front
+    pub fn open(&self) -> io::Result<(Compression, InputReader)> {
+        match self {
+            InputSource::File(path) => open_input(path),
+            InputSource::Stdin => {
+                let mut stdin = io::stdin();
+                let mut magic = Vec::with_capacity(6);
+                stdin.by_ref().take(6).read_to_end(&mut magic)?;
+                let compression = Compression::from_magic(&magic).unwrap_or(Compression::None);
+                let reader = compression.decoder(Cursor::new(magic).chain(stdin))?;
+                Ok((compression, reader))
+            }
+        }
+    }
+}
+
+// Turn command line inputs into sources: `-` for stdin, files, glob patterns and
+// directories (searched recursively for data files, in path order)
+pub fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<InputSource>, Box<dyn Error>> {
+    let mut sources = Vec::new();
+
+    for input in inputs {
+        if input.as_os_str() == "-" {
+            if sources.contains(&InputSource::Stdin) {
+                return Err("stdin (-) can only be given once".into());
+            }
+            sources.push(InputSource::Stdin);
+        } else if input.is_dir() {
+            let mut files = Vec::new();
+            collect_data_files(input, &mut files)?;
+            if files.is_empty() {
+                return Err(format!("No csv files found in directory: {}", input.display()).into());
+            }
+            files.sort();
+            sources.extend(files.into_iter().map(InputSource::File));
+        } else if !input.exists() && is_glob_pattern(input) {
+            let pattern = input.to_string_lossy();
+            let mut files = Vec::new();
+            for entry in glob::glob(&pattern)? {
+                let path = entry?;
+                if path.is_file() && has_data_extension(&path) {
+                    files.push(path);
+                }
+            }
+            if files.is_empty() {
+                return Err(format!("No csv files match pattern: {}", pattern).into());
+            }
+            sources.extend(files.into_iter().map(InputSource::File));
+        } else {
+            validate_data_file(input)?;
+            sources.push(InputSource::File(input.clone()));
+        }
+    }
+
+    Ok(sources)
+}
+
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+// Hidden files and directories (`.crc` files, `.git`, ...) are skipped
+fn collect_data_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            collect_data_files(&path, files)?;
+        } else if has_data_extension(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// A single explicitly named file must exist and look like delimited data
+pub fn validate_data_file(path: &Path) -> Result<(), Box<dyn Error>> {
+    // Check existence first
+    if !path.exists() {
+        return Err(Box::new(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("File does not exist: {}", path.display()),
+        )));
+    }
+
+    // Check if it's actually a file (not a directory)
+    if !path.is_file() {
+        return Err(Box::new(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Path exists but is not a file: {}", path.display()),
+        )));
+    }
+
+    // Check file extension, compressed extracts like `data.csv.gz` are fine too
+    if has_data_extension(path) {
+        Ok(())
+    } else {
+        Err(format!(
+            "File must be a csv/tsv/psv/txt, optionally .gz/.zst/.bz2/.xz compressed: {}",
+            path.display()
+        )
+        .into())
+    }
+}
+
+// True for `data.csv`, `data.tsv.gz`, `data.txt.zst`, ...
+pub fn has_data_extension(path: &Path) -> bool {
+    let mut path = path.to_path_buf();
+    if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(Compression::from_extension)
+        .is_some()
+    {
+        path.set_extension("");
+    }
+
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| DATA_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const DATA: &[u8] = b"id,name\n1,apple\n2,pear\n";
+
+    fn compress(compression: Compression) -> Vec<u8> {
+        match compression {
+            Compression::None => DATA.to_vec(),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(DATA).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(DATA, 0).unwrap(),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(DATA).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(DATA).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_every_compression() {
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+            Compression::Xz,
+        ] {
+            let bytes = compress(compression);
+            let detected = Compression::from_magic(&bytes).unwrap_or(Compression::None);
+            assert_eq!(detected, compression);
+
+            let mut decoded = Vec::new();
+            detected
+                .decoder(io::Cursor::new(bytes))
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, DATA, "{}", compression.as_str());
+        }
+    }
+
+    #[test]
+    fn test_expand_inputs() {
+        let dir = std::env::temp_dir().join(format!("true-sight-inputs-{}", std::process::id()));
+        let nested = dir.join("date=2024-01-02");
+        fs::create_dir_all(&nested).unwrap();
+        for path in [
+            dir.join("part-1.csv"),
+            dir.join("part-0.csv.gz"),
+            nested.join("part-0.csv"),
+            dir.join("_SUCCESS"),
+            dir.join(".part-1.csv.crc"),
+        ] {
+            fs::write(path, "").unwrap();
+        }
+
+        let sources = expand_inputs(&[dir.clone(), PathBuf::from("-")]).unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                InputSource::File(nested.join("part-0.csv")),
+                InputSource::File(dir.join("part-0.csv.gz")),
+                InputSource::File(dir.join("part-1.csv")),
+                InputSource::Stdin,
+            ]
+        );
+
+        let pattern = dir.join("part-*.csv*");
+        assert_eq!(expand_inputs(&[pattern]).unwrap().len(), 2);
+
+        assert!(expand_inputs(&[dir.join("nothing-*.csv")]).is_err());
+        assert!(expand_inputs(&[PathBuf::from("-"), PathBuf::from("-")]).is_err());
+        assert!(expand_inputs(&[dir.join("_SUCCESS")]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_has_data_extension() {
+        assert!(has_data_extension(Path::new("data.csv")));
+        assert!(has_data_extension(Path::new("data.CSV.gz")));
+        assert!(has_data_extension(Path::new("data.tsv.zst")));
+        assert!(!has_data_extension(Path::new("data.gz")));
+        assert!(!has_data_extension(Path::new("data.json")));
+        assert!(!has_data_extension(Path::new("data")));
+    }
+}
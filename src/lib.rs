@@ -1,29 +1,141 @@
 use csv::{Reader, ReaderBuilder};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
-use std::fs::File;
 use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
-use std::sync::Arc;
-use std::sync::Mutex;
+use std::sync::{mpsc, Arc};
 use std::time::Duration;
 
+use profile::{merge_profiles, CellObservation};
+use utf8::RawChunk;
+
+pub mod config;
+pub mod dates;
+pub mod duplicates;
+pub mod encoding;
+pub mod findings;
+pub mod fix;
 pub mod formatter; // Add this line to declare the module
+pub mod inference;
+pub mod input;
+pub mod mapped;
+pub mod nulls;
+pub mod numeric;
+pub mod profile;
+pub mod quarantine;
+pub mod regex_check;
+pub mod report;
+pub mod row_rules;
+pub mod rules;
+pub mod sketch;
+pub mod sniff;
+pub mod structure;
+pub mod threshold;
+pub mod utf8;
 
 // Re-export the public functions for convenience
-pub use formatter::{print_chunk_results_spark_style, SparkStyleFormatter};
+pub use config::ConfigFile;
+pub use dates::{DateProfile, DateWindow};
+pub use duplicates::{DuplicateConfig, DuplicateDetector, DuplicateReport};
+pub use encoding::{decode_input, detect_encoding, parse_encoding_label, InputEncoding};
+pub use findings::{FindingsConfig, FindingsFormat, FindingsWriter};
+pub use fix::{fix_csv, FixOptions, FixStats, RecordFixer};
+pub use formatter::{print_report_spark_style, SparkStyleFormatter};
+pub use inference::InferredType;
+pub use input::{expand_inputs, open_input, Compression, InputReader, InputSource};
+pub use mapped::{process_mapped_chunks, MappedCsv};
+pub use nulls::{NullConfig, NullVocabulary};
+pub use numeric::NumericStats;
+pub use profile::ColumnProfile;
+pub use quarantine::{QuarantineConfig, QuarantineWriter, RawBuffer, RawCapture};
+pub use regex_check::RegexCheck;
+pub use report::{DatasetJsonReport, FileMetadata, JsonReport};
+pub use row_rules::RowRule;
+pub use rules::{ColumnRules, RuleResult, RuleSet};
+pub use sniff::{sniff_file, sniff_reader, Dialect, LineTerminator};
+pub use structure::{RaggedRow, RowShapeStats};
+pub use threshold::{evaluate_thresholds, ThresholdRule, ThresholdViolation};
+pub use utf8::{ByteChunkIterator, InvalidUtf8Cell, InvalidUtf8Stats};
+
+pub fn prepare_csv_reader(
+    path: &Path,
+) -> Result<(Vec<String>, Reader<InputReader>), Box<dyn Error>> {
+    prepare_csv_reader_with_options(path, &ReaderOptions::default())
+}
 
-pub fn prepare_csv_reader(path: &Path) -> Result<(Vec<String>, Reader<File>), Box<dyn Error>> {
-    let file = File::open(path)?;
-    let mut rdr: csv::Reader<File> = ReaderBuilder::new().from_reader(file);
+// How the input should be parsed
+#[derive(Debug, Clone, Default)]
+pub struct ReaderOptions {
+    pub flexible: bool, // Accept rows with a different field count than the header
+    pub dialect: Dialect,
+    pub byte_records: bool, // Read ByteRecords, invalid UTF-8 is reported instead of aborting
+}
 
-    // Get the headers and convert them to owned Strings
-    let headers: Vec<String> = rdr.headers()?.iter().map(|s| s.to_string()).collect();
+pub fn prepare_csv_reader_with_options(
+    path: &Path,
+    options: &ReaderOptions,
+) -> Result<(Vec<String>, Reader<InputReader>), Box<dyn Error>> {
+    // Compressed files are decoded while they are read, nothing is written to disk
+    let (_compression, input) = open_input(path)?;
+    prepare_csv_reader_from(input, options)
+}
 
+// Build the csv reader on an already opened (and possibly sniffed) input
+pub fn prepare_csv_reader_from(
+    input: InputReader,
+    options: &ReaderOptions,
+) -> Result<(Vec<String>, Reader<InputReader>), Box<dyn Error>> {
+    let mut rdr: csv::Reader<InputReader> = csv_reader_builder(options).from_reader(input);
+    let headers = header_names(&mut rdr, options)?;
     Ok((headers, rdr))
 }
 
+// Reader settings shared by the streaming and the memory mapped paths
+fn csv_reader_builder(options: &ReaderOptions) -> ReaderBuilder {
+    let dialect = &options.dialect;
+    let mut builder = ReaderBuilder::new();
+    builder
+        .flexible(options.flexible)
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .has_headers(dialect.has_headers)
+        .terminator(dialect.line_terminator.to_csv_terminator());
+    builder
+}
+
+// Get the headers and convert them to owned Strings. Without a header row the
+// first record is only peeked at, so the columns get positional names
+fn header_names<R: Read>(
+    rdr: &mut Reader<R>,
+    options: &ReaderOptions,
+) -> Result<Vec<String>, csv::Error> {
+    // Header names that aren't valid UTF-8 are decoded lossily in byte mode
+    let headers = if options.byte_records {
+        csv::StringRecord::from_byte_record_lossy(rdr.byte_headers()?.clone())
+    } else {
+        rdr.headers()?.clone()
+    };
+    if options.dialect.has_headers {
+        // A UTF-8 BOM that wasn't stripped from the input would stick to the first name
+        Ok(headers
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                if i == 0 {
+                    s.trim_start_matches('\u{FEFF}')
+                } else {
+                    s
+                }
+                .to_string()
+            })
+            .collect())
+    } else {
+        Ok((0..headers.len()).map(|i| format!("col_{}", i)).collect())
+    }
+}
+
 #[test]
 fn test_csv_headers() {
     use std::path::PathBuf;
@@ -71,20 +183,33 @@ impl<R: Read> Iterator for CsvChunkIterator<'_, R> {
     type Item = Result<Vec<csv::StringRecord>, csv::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let chunk: Result<Vec<_>, _> = self.records.by_ref().take(self.chunk_size).collect(); // this essentially allows for the iterator not to reset and read from top of file again
+        next_chunk(&mut self.records, self.chunk_size)
+    }
+}
 
-        match chunk {
-            Ok(records) if records.is_empty() => None, // End of iterator, no more chunks
-            Ok(records) => {
-                // Print a message when a chunk is read
-                println!("Chunk read with {} records", records.len());
-                Some(Ok(records))
-            }
-            Err(e) => Some(Err(e)), // Propagate the error if there was one
+// Take the next chunk off a record iterator, shared by the string and byte readers
+pub(crate) fn next_chunk<T>(
+    records: &mut impl Iterator<Item = Result<T, csv::Error>>,
+    chunk_size: usize,
+) -> Option<Result<Vec<T>, csv::Error>> {
+    let chunk: Result<Vec<_>, _> = records.by_ref().take(chunk_size).collect(); // this essentially allows for the iterator not to reset and read from top of file again
+
+    match chunk {
+        Ok(records) if records.is_empty() => None, // End of iterator, no more chunks
+        Ok(records) => {
+            log_chunk_read(records.len());
+            Some(Ok(records))
         }
+        Err(e) => Some(Err(e)), // Propagate the error if there was one
     }
 }
 
+// Print a message when a chunk is read, on stderr so stdout stays clean for reports.
+// Every reader (streamed, byte records, mapped ranges) logs through here
+pub(crate) fn log_chunk_read(records: usize) {
+    eprintln!("Chunk read with {} records", records);
+}
+
 // Each check must be Send + Sync to work with Rayon
 pub trait PatternCheck: Send + Sync {
     // Name of the check (for reporting)
@@ -93,8 +218,194 @@ pub trait PatternCheck: Send + Sync {
     // The actual check logic
     fn check(&self, value: &str) -> bool;
 
+    // Check a value knowing its column, for checks with per-column settings
+    fn check_column(&self, _column: usize, value: &str) -> bool {
+        self.check(value)
+    }
+
     // Example of what this check looks for (for reporting)
     fn show_check_pattern(&self) -> &str;
+
+    // Short label used in table headings, defaults to the name
+    fn label(&self) -> &str {
+        self.name()
+    }
+
+    // Column a rule check is bound to, None for checks that run on every column
+    fn column(&self) -> Option<usize> {
+        None
+    }
+}
+
+// Column names of the header row, so row checks can find fields by name
+#[derive(Debug, Clone, Default)]
+pub struct HeaderLookup {
+    headers: Vec<String>,
+    indexes: HashMap<String, usize>,
+}
+
+impl HeaderLookup {
+    pub fn new(headers: &[String]) -> Self {
+        let mut indexes = HashMap::new();
+        for (i, header) in headers.iter().enumerate() {
+            // The first of two columns with the same name wins
+            indexes.entry(header.clone()).or_insert(i);
+        }
+        Self {
+            headers: headers.to_vec(),
+            indexes,
+        }
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indexes.get(name).copied()
+    }
+
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.headers.get(index).map(|h| h.as_str())
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    // Field of a record by column name
+    pub fn get<'r>(&self, record: &'r csv::StringRecord, name: &str) -> Option<&'r str> {
+        record.get(self.index(name)?)
+    }
+}
+
+// A check over a whole row, for rules that involve more than one field
+pub trait RowCheck: Send + Sync {
+    fn name(&self) -> &str;
+
+    // True if the row breaks the check
+    fn check_row(&self, record: &csv::StringRecord, headers: &HeaderLookup) -> bool;
+
+    // The rule as written (for reporting)
+    fn show_check_pattern(&self) -> &str;
+
+    // Column the violations are counted against, the first one the check reads
+    fn column(&self) -> usize;
+}
+
+// Registry of the checks that get run against every cell.
+// Results are stored per check keyed by `PatternCheck::name()`, so names must be unique.
+#[derive(Clone)]
+pub struct CheckRegistry {
+    checks: Vec<Arc<dyn PatternCheck>>,
+    row_checks: Vec<Arc<dyn RowCheck>>, // counted under their name like the cell checks
+    headers: Arc<HeaderLookup>,         // header row the row checks were built for
+    date_window: DateWindow,            // plausible dates, used while profiling date columns
+}
+
+impl Default for CheckRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheckRegistry {
+    // Registry with the built in NULL-like, Empty and Whitespace checks
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.checks.push(Arc::new(NullLikeCheck::new()));
+        registry.checks.push(Arc::new(EmptyCheck::new()));
+        registry.checks.push(Arc::new(WhiteSpaceOnlyCheck::new()));
+        registry
+    }
+
+    // Same built in checks, with a configured NULL-like check
+    pub fn with_null_check(null_check: NullLikeCheck) -> Self {
+        let mut registry = Self::new();
+        registry.checks[0] = Arc::new(null_check);
+        registry
+    }
+
+    // Registry with no checks at all
+    pub fn empty() -> Self {
+        Self {
+            checks: Vec::new(),
+            row_checks: Vec::new(),
+            headers: Arc::new(HeaderLookup::default()),
+            date_window: DateWindow::default(),
+        }
+    }
+
+    pub fn register<C: PatternCheck + 'static>(&mut self, check: C) -> Result<(), Box<dyn Error>> {
+        self.register_shared(Arc::new(check))
+    }
+
+    pub fn register_shared(&mut self, check: Arc<dyn PatternCheck>) -> Result<(), Box<dyn Error>> {
+        self.ensure_unique(check.name())?;
+        self.checks.push(check);
+        Ok(())
+    }
+
+    pub fn register_row_check(&mut self, check: Arc<dyn RowCheck>) -> Result<(), Box<dyn Error>> {
+        self.ensure_unique(check.name())?;
+        self.row_checks.push(check);
+        Ok(())
+    }
+
+    fn ensure_unique(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        if self.get(name).is_some() || self.get_row_check(name).is_some() {
+            return Err(format!("A check named '{}' is already registered", name).into());
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn PatternCheck>> {
+        self.checks.iter().find(|check| check.name() == name)
+    }
+
+    pub fn get_row_check(&self, name: &str) -> Option<&Arc<dyn RowCheck>> {
+        self.row_checks.iter().find(|check| check.name() == name)
+    }
+
+    pub fn row_checks(&self) -> &[Arc<dyn RowCheck>] {
+        &self.row_checks
+    }
+
+    // Header row handed to the row checks
+    pub fn set_headers(&mut self, headers: &[String]) {
+        self.headers = Arc::new(HeaderLookup::new(headers));
+    }
+
+    pub fn headers(&self) -> &HeaderLookup {
+        &self.headers
+    }
+
+    // Checks in registration order
+    pub fn checks(&self) -> &[Arc<dyn PatternCheck>] {
+        &self.checks
+    }
+
+    // Checks that run on every column, these get the per-check report tables
+    pub fn general_checks(&self) -> impl Iterator<Item = &Arc<dyn PatternCheck>> {
+        self.checks.iter().filter(|check| check.column().is_none())
+    }
+
+    // Checks bound to a single column, e.g. compiled from the rules file
+    pub fn column_checks(&self) -> impl Iterator<Item = &Arc<dyn PatternCheck>> {
+        self.checks.iter().filter(|check| check.column().is_some())
+    }
+
+    pub fn set_date_window(&mut self, window: DateWindow) {
+        self.date_window = window;
+    }
+
+    pub fn date_window(&self) -> &DateWindow {
+        &self.date_window
+    }
+
+    pub fn len(&self) -> usize {
+        self.checks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
 }
 
 // Empty Check Strategy
@@ -107,6 +418,8 @@ impl Default for EmptyCheck {
 }
 
 impl EmptyCheck {
+    pub const NAME: &'static str = "Empty";
+
     pub fn new() -> Self {
         Self
     }
@@ -114,7 +427,7 @@ impl EmptyCheck {
 
 impl PatternCheck for EmptyCheck {
     fn name(&self) -> &str {
-        "Empty"
+        Self::NAME
     }
     fn check(&self, value: &str) -> bool {
         value.is_empty()
@@ -122,6 +435,9 @@ impl PatternCheck for EmptyCheck {
     fn show_check_pattern(&self) -> &str {
         "Empty string \"\""
     }
+    fn label(&self) -> &str {
+        "Empty"
+    }
 }
 
 pub struct WhiteSpaceOnlyCheck;
@@ -133,6 +449,8 @@ impl Default for WhiteSpaceOnlyCheck {
 }
 
 impl WhiteSpaceOnlyCheck {
+    pub const NAME: &'static str = "WhiteSpaceOnlyCheck";
+
     pub fn new() -> Self {
         Self
     }
@@ -140,7 +458,7 @@ impl WhiteSpaceOnlyCheck {
 
 impl PatternCheck for WhiteSpaceOnlyCheck {
     fn name(&self) -> &str {
-        "WhiteSpaceOnlyCheck"
+        Self::NAME
     }
     fn check(&self, value: &str) -> bool {
         !value.is_empty() && value.trim().is_empty()
@@ -148,10 +466,19 @@ impl PatternCheck for WhiteSpaceOnlyCheck {
     fn show_check_pattern(&self) -> &str {
         "WhiteSpaceOnlyCheck string ' ' "
     }
+    fn label(&self) -> &str {
+        "Whitespace"
+    }
 }
 
-// NULL Like Values Check Strategy
-pub struct NullLikeCheck;
+// NULL Like Values Check Strategy. Uses NULL_LIKE_VALUES unless built from a user
+// list (see nulls::NullConfig), columns can have a list of their own
+#[derive(Debug, Clone)]
+pub struct NullLikeCheck {
+    values: NullVocabulary,
+    columns: Vec<Option<NullVocabulary>>, // indexed by column, None uses `values`
+    pattern: String,
+}
 
 impl Default for NullLikeCheck {
     fn default() -> Self {
@@ -160,99 +487,167 @@ impl Default for NullLikeCheck {
 }
 
 impl NullLikeCheck {
+    pub const NAME: &'static str = "NULL_LIKE_VALUES";
     pub const NULL_LIKE_VALUES: [&'static str; 5] = ["NULL", "N/A", "NA", "NONE", "NaN"]; // use const since only checks a few strings
 
     pub fn new() -> Self {
-        Self
+        Self::with_values(Self::NULL_LIKE_VALUES, false)
+    }
+
+    // Replace the built in list
+    pub fn with_values<I, S>(values: I, case_sensitive: bool) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let values = NullVocabulary::new(values, case_sensitive);
+        let pattern = values.describe();
+        Self {
+            values,
+            columns: Vec::new(),
+            pattern,
+        }
+    }
+
+    // Use a different list for one column, `name` is only used for show_check_pattern
+    pub fn with_column_values<I, S>(mut self, column: usize, name: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let vocabulary = NullVocabulary::new(values, self.values.case_sensitive());
+        self.pattern
+            .push_str(&format!("; {}: {}", name, vocabulary.values().join(", ")));
+        if self.columns.len() <= column {
+            self.columns.resize(column + 1, None);
+        }
+        self.columns[column] = Some(vocabulary);
+        self
+    }
+
+    // The values treated as NULL in a column
+    pub fn vocabulary(&self, column: usize) -> &NullVocabulary {
+        self.columns
+            .get(column)
+            .and_then(Option::as_ref)
+            .unwrap_or(&self.values)
     }
 }
 
 impl PatternCheck for NullLikeCheck {
     fn name(&self) -> &str {
-        "NULL_LIKE_VALUES"
+        Self::NAME
     }
 
     fn check(&self, value: &str) -> bool {
-        let trimmed = value.trim(); // Borrowed slice, no allocation
-        Self::NULL_LIKE_VALUES
-            .iter()
-            .any(|&null| trimmed.eq_ignore_ascii_case(null))
+        self.values.matches(value)
+    }
+
+    fn check_column(&self, column: usize, value: &str) -> bool {
+        self.vocabulary(column).matches(value)
     }
 
     fn show_check_pattern(&self) -> &str {
-        "NULL, N/A, NA, None, NaN" //TODO: Can we ref the const here? avoid hardcode would need to change in trait as well
+        &self.pattern
+    }
+
+    fn label(&self) -> &str {
+        "NULL-like"
     }
 }
 
 // Create a struct to hold statistics for each column
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct ColumnStats {
-    null_like_count: usize,
-    empty_count: usize,
-    white_space_only_count: usize, // Add other statistics as needed (pattern matches, etc.)
+    check_counts: HashMap<String, usize>, // Findings per check keyed by PatternCheck::name()
+    profile: ColumnProfile,
+}
+
+impl ColumnStats {
+    pub fn count_for(&self, check_name: &str) -> usize {
+        self.check_counts.get(check_name).copied().unwrap_or(0)
+    }
+
+    pub fn profile(&self) -> &ColumnProfile {
+        &self.profile
+    }
 }
 
 #[derive(Clone)]
 pub struct CsvAggregator {
     headers: Vec<String>,
+    registry: CheckRegistry,
     column_stats: Vec<ColumnStats>,
     total_rows: usize,
+    total_chunks: usize,
     chunk_size: usize,
     processing_time: Option<Duration>,
+    row_shape: RowShapeStats,
+    invalid_utf8: InvalidUtf8Stats,
 }
 
 impl CsvAggregator {
-    // Initialize with headers
-    pub fn new(headers: Vec<String>, chunk_size: usize) -> Self {
+    // Initialize with headers and the checks whose results will be aggregated
+    pub fn new(headers: Vec<String>, chunk_size: usize, registry: &CheckRegistry) -> Self {
         let column_count = headers.len();
-        let column_stats = vec![
-            ColumnStats {
-                null_like_count: 0,
-                empty_count: 0,
-                white_space_only_count: 0
-            };
-            column_count
-        ];
+        let column_stats = vec![ColumnStats::default(); column_count];
 
         CsvAggregator {
             headers,
+            registry: registry.clone(),
             column_stats,
             total_rows: 0,
+            total_chunks: 0,
             chunk_size,
             processing_time: None,
+            row_shape: RowShapeStats::default(),
+            invalid_utf8: InvalidUtf8Stats::default(),
         }
     }
 
     // Add chunk results to aggregator
-    pub fn add_chunk_results(
-        &mut self,
-        null_map: &HashMap<usize, usize>,
-        empty_map: &HashMap<usize, usize>,
-        white_space_only_map: &HashMap<usize, usize>,
-        chunk_size: usize,
-    ) {
-        // Update total row count
-        self.total_rows += chunk_size;
-
-        // Update null-like counts
-        for (&col, &count) in null_map.iter() {
-            if col < self.column_stats.len() {
-                self.column_stats[col].null_like_count += count;
+    pub fn add_chunk_results(&mut self, result: &ChunkProcessingResult) {
+        // Update total row and chunk count
+        self.total_rows += result.rows_processed;
+        self.total_chunks += 1;
+        self.row_shape.merge(&result.row_shape);
+        self.invalid_utf8.merge(&result.invalid_utf8);
+
+        // Update per check counts
+        for (check_name, counts) in result.check_counts.iter() {
+            for (&col, &count) in counts.iter() {
+                if col < self.column_stats.len() {
+                    *self.column_stats[col]
+                        .check_counts
+                        .entry(check_name.clone())
+                        .or_insert(0) += count;
+                }
             }
         }
 
-        // Update empty counts
-        for (&col, &count) in empty_map.iter() {
-            if col < self.column_stats.len() {
-                self.column_stats[col].empty_count += count;
-            }
+        // Merge column profiles, extra fields past the headers are ignored like the counts
+        for (stats, profile) in self.column_stats.iter_mut().zip(&result.column_profiles) {
+            stats.profile.merge(profile);
         }
+    }
 
-        // Update white_space_only_map counts
-        for (&col, &count) in white_space_only_map.iter() {
-            if col < self.column_stats.len() {
-                self.column_stats[col].white_space_only_count += count;
+    // Fold in a partial run, e.g. another file of the same dataset. Both must have been
+    // built for the same headers and checks
+    pub fn merge(&mut self, other: CsvAggregator) {
+        self.total_rows += other.total_rows;
+        self.total_chunks += other.total_chunks;
+        self.row_shape.merge(&other.row_shape);
+        self.invalid_utf8.merge(&other.invalid_utf8);
+        self.processing_time = match (self.processing_time, other.processing_time) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+
+        for (stats, other) in self.column_stats.iter_mut().zip(other.column_stats) {
+            for (check_name, count) in other.check_counts {
+                *stats.check_counts.entry(check_name).or_insert(0) += count;
             }
+            stats.profile.merge(&other.profile);
         }
     }
 
@@ -260,6 +655,57 @@ impl CsvAggregator {
         self.processing_time = Some(duration);
     }
 
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    pub fn registry(&self) -> &CheckRegistry {
+        &self.registry
+    }
+
+    pub fn column_stats(&self) -> &[ColumnStats] {
+        &self.column_stats
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.total_rows
+    }
+
+    pub fn total_chunks(&self) -> usize {
+        self.total_chunks
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    pub fn processing_time(&self) -> Option<Duration> {
+        self.processing_time
+    }
+
+    pub fn row_shape(&self) -> &RowShapeStats {
+        &self.row_shape
+    }
+
+    pub fn invalid_utf8(&self) -> &InvalidUtf8Stats {
+        &self.invalid_utf8
+    }
+
+    // Findings of a single check in one column
+    pub fn count_for(&self, check_name: &str, column: usize) -> usize {
+        self.column_stats
+            .get(column)
+            .map_or(0, |stats| stats.count_for(check_name))
+    }
+
+    // Total findings for a single check across all columns
+    pub fn total_for(&self, check_name: &str) -> usize {
+        self.column_stats
+            .iter()
+            .map(|stats| stats.count_for(check_name))
+            .sum()
+    }
+
     // Generate final report
     pub fn generate_report(&self) -> String {
         let mut report = String::new();
@@ -297,6 +743,21 @@ impl CsvAggregator {
             }
         }
 
+        if self.row_shape.ragged_rows() > 0 {
+            report.push_str(&format!(
+                "Ragged rows: {} ({} short, {} long)\n",
+                self.row_shape.ragged_rows(),
+                self.row_shape.short_rows,
+                self.row_shape.long_rows
+            ));
+        }
+        if self.invalid_utf8.invalid_cells() > 0 {
+            report.push_str(&format!(
+                "Invalid UTF-8 cells: {}\n",
+                self.invalid_utf8.invalid_cells()
+            ));
+        }
+
         report.push_str("COLUMN STATISTICS:\n");
         for (i, header) in self.headers.iter().enumerate() {
             let stats = &self.column_stats[i];
@@ -306,39 +767,70 @@ impl CsvAggregator {
 
             report.push_str(&format!("col_{} ('{}'):\n", i, header));
 
-            // Calculate percentages
-            let null_percent = if self.total_rows > 0 {
-                (stats.null_like_count as f64 / self.total_rows as f64) * 100.0
-            } else {
-                0.0
-            };
+            for check in self.registry.general_checks() {
+                let count = stats.count_for(check.name());
 
-            let empty_percent = if self.total_rows > 0 {
-                (stats.empty_count as f64 / self.total_rows as f64) * 100.0
-            } else {
-                0.0
-            };
+                // Calculate percentages
+                let percent = if self.total_rows > 0 {
+                    (count as f64 / self.total_rows as f64) * 100.0
+                } else {
+                    0.0
+                };
 
-            let white_space_only_percent = if self.total_rows > 0 {
-                (stats.white_space_only_count as f64 / self.total_rows as f64) * 100.0
-            } else {
-                0.0
-            };
+                report.push_str(&format!(
+                    "  {} values: {} ({:.2}%)\n",
+                    check.label(),
+                    count,
+                    percent
+                ));
+            }
 
-            report.push_str(&format!(
-                "  NULL-like values: {} ({:.2}%)\n",
-                stats.null_like_count, null_percent
-            ));
+            let types = &stats.profile.types;
+            match types.inferred() {
+                Some(inferred) => {
+                    let mismatches = types.mismatch_count();
+                    let mismatch_percent = if self.total_rows > 0 {
+                        (mismatches as f64 / self.total_rows as f64) * 100.0
+                    } else {
+                        0.0
+                    };
+                    report.push_str(&format!(
+                        "  Inferred type: {} ({} non-conforming values, {:.2}%)\n",
+                        inferred.as_str(),
+                        mismatches,
+                        mismatch_percent
+                    ));
+                }
+                None => report.push_str("  Inferred type: - (no non-missing values)\n"),
+            }
 
-            report.push_str(&format!(
-                "  Empty values: {} ({:.2}%)\n",
-                stats.empty_count, empty_percent
-            ));
+            let non_missing = stats.profile.types.non_missing();
+            if non_missing > 0 {
+                report.push_str(&format!(
+                    "  Approx distinct values: {} of {} non-missing\n",
+                    stats.profile.distinct.estimate().min(non_missing),
+                    non_missing
+                ));
+                if let Some((value, count)) = stats.profile.top_values.top(1).first() {
+                    report.push_str(&format!("  Most frequent value: '{}' ({})\n", value, count));
+                }
+            }
 
-            report.push_str(&format!(
-                "  White-Space-Only values: {} ({:.2}%)\n",
-                stats.white_space_only_count, white_space_only_percent
-            ));
+            if stats.profile.is_numeric() {
+                let numeric = &stats.profile.numeric;
+                if let (Some(min), Some(max), Some(mean)) =
+                    (numeric.min(), numeric.max(), numeric.mean())
+                {
+                    report.push_str(&format!(
+                        "  Numeric: min {} max {} mean {:.4} stddev {:.4} median ~{:.4}\n",
+                        min,
+                        max,
+                        mean,
+                        numeric.stddev().unwrap_or(0.0),
+                        numeric.quantile(0.5).unwrap_or(mean)
+                    ));
+                }
+            }
 
             report.push('\n');
         }
@@ -352,15 +844,44 @@ impl CsvAggregator {
 pub struct ChunkProcessingResult {
     pub chunk_number: usize,
     pub rows_processed: usize,
-    pub null_counts: HashMap<usize, usize>,
-    pub empty_counts: HashMap<usize, usize>,
-    pub whitespace_counts: HashMap<usize, usize>,
+    pub check_counts: HashMap<String, HashMap<usize, usize>>, // column -> count, keyed by PatternCheck::name()
+    pub column_profiles: Vec<ColumnProfile>,                  // indexed by column
+    pub row_shape: RowShapeStats,                             // rows with an unexpected field count
+    pub invalid_utf8: InvalidUtf8Stats, // only filled when reading ByteRecords
+    pub findings: Vec<CellFinding>,     // only filled when an observer wants findings
+}
+
+// A cell flagged by a check, recorded by the worker that checked the chunk so observers
+// writing findings don't have to run the checks again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellFinding {
+    pub row: usize,    // index of the record in its chunk
+    pub column: usize, // row checks are reported on their first column
+    pub check: usize,  // index into the registry's checks, row checks follow the cell checks
+}
+
+impl ChunkProcessingResult {
+    // Per-column counts for a single check
+    pub fn counts_for(&self, check_name: &str) -> Option<&HashMap<usize, usize>> {
+        self.check_counts.get(check_name)
+    }
+
+    // Total findings for a single check across all columns
+    pub fn total_for(&self, check_name: &str) -> usize {
+        self.counts_for(check_name)
+            .map(|counts| counts.values().sum())
+            .unwrap_or(0)
+    }
 }
 
 // Struct to hold overall processing configuration
 pub struct ProcessingConfig {
     pub chunk_size: usize,
     pub enable_parallel: bool,
+    pub registry: CheckRegistry,
+    pub expected_fields: Option<usize>, // Header length, rows that differ are reported as ragged
+    pub max_in_flight_chunks: usize, // Chunks read but not merged yet, bounds memory when parallel
+    pub record_findings: bool,       // Keep every flagged cell, set when an observer wants findings
 }
 
 impl Default for ProcessingConfig {
@@ -368,37 +889,105 @@ impl Default for ProcessingConfig {
         Self {
             chunk_size: 1_000_000,
             enable_parallel: true,
+            registry: CheckRegistry::new(),
+            expected_fields: None,
+            max_in_flight_chunks: 2,
+            record_findings: false,
         }
     }
 }
 
-// Main processing function
-pub fn process_csv_chunks<R: Read>(
+// Sees every chunk in file order after it has been checked. Used for features that
+// need whole rows across the entire file rather than per-chunk counts (e.g. duplicates).
+pub trait ChunkObserver {
+    fn observe_chunk(&mut self, records: &[csv::StringRecord]) -> Result<(), Box<dyn Error>>;
+
+    // Observers that write row-level findings return true, the workers then record the
+    // flagged cells while they check the chunk
+    fn wants_findings(&self) -> bool {
+        false
+    }
+
+    // Cells of the chunk flagged by the checks in file order, called right after
+    // observe_chunk when wants_findings is true
+    fn observe_findings(
+        &mut self,
+        _records: &[csv::StringRecord],
+        _findings: &[CellFinding],
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // Cells of the chunk that weren't valid UTF-8, called after observe_chunk
+    fn observe_invalid_utf8(&mut self, _stats: &InvalidUtf8Stats) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // Called once after the last chunk
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+// Main processing function, every chunk is merged into the aggregator as soon as it
+// is checked so memory doesn't grow with the number of chunks
+pub fn process_csv_chunks<R: Read + Send>(
     chunk_iterator: CsvChunkIterator<'_, R>,
     config: ProcessingConfig,
-) -> Result<Vec<ChunkProcessingResult>, Box<dyn std::error::Error>> {
-    let null_check = Arc::new(NullLikeCheck::new());
-    let empty_check = Arc::new(EmptyCheck::new());
-    let white_space_only_check = Arc::new(WhiteSpaceOnlyCheck::new());
+    aggregator: &mut CsvAggregator,
+) -> Result<(), Box<dyn std::error::Error>> {
+    process_csv_chunks_with_observers(chunk_iterator, config, aggregator, &mut [])
+}
 
-    let mut results = Vec::new();
+// Same as process_csv_chunks, but also hands every chunk to the observers
+pub fn process_csv_chunks_with_observers<R: Read + Send>(
+    chunk_iterator: CsvChunkIterator<'_, R>,
+    config: ProcessingConfig,
+    aggregator: &mut CsvAggregator,
+    observers: &mut [&mut dyn ChunkObserver],
+) -> Result<(), Box<dyn std::error::Error>> {
+    process_chunks(chunk_iterator, config, aggregator, observers)
+}
+
+// Byte record version of process_csv_chunks, cells that aren't valid UTF-8 are counted
+// per column instead of stopping the run
+pub fn process_byte_chunks<R: Read + Send>(
+    chunk_iterator: ByteChunkIterator<'_, R>,
+    config: ProcessingConfig,
+    aggregator: &mut CsvAggregator,
+) -> Result<(), Box<dyn std::error::Error>> {
+    process_byte_chunks_with_observers(chunk_iterator, config, aggregator, &mut [])
+}
+
+pub fn process_byte_chunks_with_observers<R: Read + Send>(
+    chunk_iterator: ByteChunkIterator<'_, R>,
+    config: ProcessingConfig,
+    aggregator: &mut CsvAggregator,
+    observers: &mut [&mut dyn ChunkObserver],
+) -> Result<(), Box<dyn std::error::Error>> {
+    process_chunks(chunk_iterator, config, aggregator, observers)
+}
+
+fn process_chunks<C: RawChunk>(
+    chunks: impl Iterator<Item = Result<C, csv::Error>> + Send,
+    mut config: ProcessingConfig,
+    aggregator: &mut CsvAggregator,
+    observers: &mut [&mut dyn ChunkObserver],
+) -> Result<(), Box<dyn std::error::Error>> {
+    config.record_findings |= wants_findings(observers);
+    if config.enable_parallel {
+        return process_pipelined(chunks, config, aggregator, observers);
+    }
     let mut chunk_number = 0;
 
-    for chunk in chunk_iterator {
+    for chunk in chunks {
         match chunk {
-            Ok(records) => {
+            Ok(chunk) => {
                 chunk_number += 1;
 
-                let result = process_single_chunk(
-                    &records,
-                    chunk_number,
-                    &null_check,
-                    &empty_check,
-                    &white_space_only_check,
-                    config.enable_parallel,
-                )?;
-
-                results.push(result);
+                let (records, result) = check_chunk(chunk, chunk_number, &config, false)?;
+                observe_checked_chunk(observers, &records, &result)?;
+                aggregator.add_chunk_results(&result);
             }
             Err(e) => {
                 return Err(Box::new(e));
@@ -406,215 +995,340 @@ pub fn process_csv_chunks<R: Read>(
         }
     }
 
-    Ok(results)
+    for observer in observers.iter_mut() {
+        observer.finish()?;
+    }
+
+    Ok(())
 }
 
-// Process a single chunk
-pub fn process_single_chunk(
-    records: &[csv::StringRecord],
+// Decode and check one chunk, with the row shape when the header length is known
+fn check_chunk<C: RawChunk>(
+    chunk: C,
     chunk_number: usize,
-    null_check: &Arc<NullLikeCheck>,
-    empty_check: &Arc<EmptyCheck>,
-    whitespace_check: &Arc<WhiteSpaceOnlyCheck>,
+    config: &ProcessingConfig,
     enable_parallel: bool,
-) -> Result<ChunkProcessingResult, Box<dyn std::error::Error>> {
-    let null_counters = Arc::new(Mutex::new(HashMap::<usize, usize>::new()));
-    let empty_counters = Arc::new(Mutex::new(HashMap::<usize, usize>::new()));
-    let whitespace_counters = Arc::new(Mutex::new(HashMap::<usize, usize>::new()));
-
-    if enable_parallel {
-        records.par_iter().for_each(|record| {
-            process_record(
-                record,
-                &null_counters,
-                &empty_counters,
-                &whitespace_counters,
-                null_check,
-                empty_check,
-                whitespace_check,
-            );
-        });
-    } else {
-        records.iter().for_each(|record| {
-            process_record(
-                record,
-                &null_counters,
-                &empty_counters,
-                &whitespace_counters,
-                null_check,
-                empty_check,
-                whitespace_check,
-            );
-        });
-    }
-
-    // Extract results from Arc<Mutex<>>
-    let null_counts = null_counters.lock().unwrap().clone();
-    let empty_counts = empty_counters.lock().unwrap().clone();
-    let whitespace_counts = whitespace_counters.lock().unwrap().clone();
-
-    Ok(ChunkProcessingResult {
+) -> Result<(Vec<csv::StringRecord>, ChunkProcessingResult), Box<dyn std::error::Error>> {
+    let (records, invalid_utf8) = chunk.decode();
+    let mut result = check_records(
+        &records,
         chunk_number,
-        rows_processed: records.len(),
-        null_counts,
-        empty_counts,
-        whitespace_counts,
-    })
+        &config.registry,
+        enable_parallel,
+        config.record_findings,
+    );
+    if let Some(expected_fields) = config.expected_fields {
+        result.row_shape = RowShapeStats::from_records(&records, expected_fields);
+    }
+    result.invalid_utf8 = invalid_utf8;
+    Ok((records, result))
 }
 
-// Process a single record - the core logic
-fn process_record(
-    record: &csv::StringRecord,
-    null_counters: &Arc<Mutex<HashMap<usize, usize>>>,
-    empty_counters: &Arc<Mutex<HashMap<usize, usize>>>,
-    whitespace_counters: &Arc<Mutex<HashMap<usize, usize>>>,
-    null_check: &Arc<NullLikeCheck>,
-    empty_check: &Arc<EmptyCheck>,
-    whitespace_check: &Arc<WhiteSpaceOnlyCheck>,
-) {
-    let mut local_null_findings = Vec::new();
-    let mut local_empty_findings = Vec::new();
-    let mut local_whitespace_findings = Vec::new();
+fn wants_findings(observers: &[&mut dyn ChunkObserver]) -> bool {
+    observers.iter().any(|observer| observer.wants_findings())
+}
 
-    for (i, field) in record.iter().enumerate() {
-        if null_check.check(field) {
-            local_null_findings.push(i);
-        }
-        if empty_check.check(field) {
-            local_empty_findings.push(i);
+// Hand a checked chunk to the observers, in file order
+fn observe_checked_chunk(
+    observers: &mut [&mut dyn ChunkObserver],
+    records: &[csv::StringRecord],
+    result: &ChunkProcessingResult,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for observer in observers.iter_mut() {
+        observer.observe_chunk(records)?;
+        if observer.wants_findings() {
+            observer.observe_findings(records, &result.findings)?;
         }
-        if whitespace_check.check(field) {
-            local_whitespace_findings.push(i);
+        if result.invalid_utf8.invalid_cells() > 0 {
+            observer.observe_invalid_utf8(&result.invalid_utf8)?;
         }
     }
+    Ok(())
+}
 
-    // Update counters
-    if !local_null_findings.is_empty() {
-        let mut null_map = null_counters.lock().unwrap();
-        for col in local_null_findings {
-            *null_map.entry(col).or_insert(0) += 1;
-        }
-    }
+// The message of a panic payload, which is a &str or a String for panic!() calls
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+// A chunk after the worker pool is done with it
+type CheckedChunk = (
+    usize,
+    Result<(Vec<csv::StringRecord>, ChunkProcessingResult), String>,
+);
+
+// Reading and checking overlap: a reader thread parses the next chunks while the rayon
+// pool checks earlier ones. Checked chunks are merged and handed to the observers in
+// chunk order, so the output is the same as a sequential run. At most
+// `max_in_flight_chunks` chunks are dispatched and not yet merged, plus the one the
+// reader is filling.
+fn process_pipelined<C: RawChunk>(
+    chunks: impl Iterator<Item = Result<C, csv::Error>> + Send,
+    config: ProcessingConfig,
+    aggregator: &mut CsvAggregator,
+    observers: &mut [&mut dyn ChunkObserver],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let max_in_flight = config.max_in_flight_chunks.max(1);
+    let config = Arc::new(config);
+
+    std::thread::scope(|scope| {
+        // Rendezvous channel, the reader only runs one chunk ahead of the dispatcher
+        let (chunk_tx, chunk_rx) = mpsc::sync_channel(0);
+        scope.spawn(move || {
+            for chunk in chunks {
+                let failed = chunk.is_err();
+                if chunk_tx.send(chunk).is_err() || failed {
+                    break;
+                }
+            }
+        });
 
-    if !local_empty_findings.is_empty() {
-        let mut empty_map = empty_counters.lock().unwrap();
-        for col in local_empty_findings {
-            *empty_map.entry(col).or_insert(0) += 1;
+        let (done_tx, done_rx) = mpsc::channel::<CheckedChunk>();
+        let mut done_tx = Some(done_tx);
+        let mut checked: BTreeMap<usize, (Vec<csv::StringRecord>, ChunkProcessingResult)> =
+            BTreeMap::new();
+        let mut chunks_read = 0;
+        let mut chunks_merged = 0;
+        let mut reading = true;
+
+        loop {
+            // Dispatch while there is room, the calling thread never blocks inside the pool
+            while reading && chunks_read - chunks_merged < max_in_flight {
+                match chunk_rx.recv() {
+                    Ok(Ok(chunk)) => {
+                        chunks_read += 1;
+                        let chunk_number = chunks_read;
+                        let config = Arc::clone(&config);
+                        let done_tx = done_tx.clone().expect("still reading");
+                        rayon::spawn(move || {
+                            // A panicking check (e.g. a custom PatternCheck) is sent back as
+                            // an error, otherwise the chunk would never arrive
+                            let checked = panic::catch_unwind(AssertUnwindSafe(|| {
+                                check_chunk(chunk, chunk_number, &config, true)
+                                    .map_err(|e| e.to_string())
+                            }))
+                            .unwrap_or_else(|payload| {
+                                Err(format!(
+                                    "Checking chunk #{} panicked: {}",
+                                    chunk_number,
+                                    panic_message(payload.as_ref())
+                                ))
+                            });
+                            // The receiver is only gone when an earlier chunk failed
+                            let _ = done_tx.send((chunk_number, checked));
+                        });
+                    }
+                    Ok(Err(e)) => return Err(Box::new(e) as Box<dyn Error>),
+                    Err(_) => reading = false, // no more chunks
+                }
+            }
+            if chunks_merged == chunks_read {
+                break;
+            }
+            if !reading {
+                // Only the workers hold senders now, so a lost chunk closes the channel
+                done_tx = None;
+            }
+
+            let (chunk_number, result) = done_rx.recv().map_err(|_| {
+                format!(
+                    "Chunk #{} was never checked, a worker stopped without a result",
+                    chunks_merged + 1
+                )
+            })?;
+            checked.insert(chunk_number, result?);
+
+            // Merge whatever is next in file order
+            while let Some((records, result)) = checked.remove(&(chunks_merged + 1)) {
+                observe_checked_chunk(observers, &records, &result)?;
+                aggregator.add_chunk_results(&result);
+                chunks_merged += 1;
+            }
         }
-    }
 
-    if !local_whitespace_findings.is_empty() {
-        let mut whitespace_map = whitespace_counters.lock().unwrap();
-        for col in local_whitespace_findings {
-            *whitespace_map.entry(col).or_insert(0) += 1;
+        for observer in observers.iter_mut() {
+            observer.finish()?;
         }
-    }
+        Ok(())
+    })
 }
 
-// Print results function
-pub fn print_chunk_results(result: &ChunkProcessingResult, headers: &[String]) {
-    println!(
-        "\nProcessed chunk #{} with {} rows",
-        result.chunk_number, result.rows_processed
-    );
-
-    // Print statistics for this chunk
-    println!("--- Statistics for chunk {}:", result.chunk_number);
-
-    // NULL-like values
-    if !result.null_counts.is_empty() {
-        println!("NULL-like values:");
-        for (col, count) in result.null_counts.iter().filter(|(_, &count)| count > 0) {
-            let header_name = if *col < headers.len() {
-                &headers[*col]
-            } else {
-                "Unknown Column"
-            };
+// Process a single chunk
+pub fn process_single_chunk(
+    records: &[csv::StringRecord],
+    chunk_number: usize,
+    registry: &CheckRegistry,
+    enable_parallel: bool,
+) -> Result<ChunkProcessingResult, Box<dyn std::error::Error>> {
+    Ok(check_records(
+        records,
+        chunk_number,
+        registry,
+        enable_parallel,
+        false,
+    ))
+}
 
-            println!(
-                "   col_{} column_name={}: {} NULL-like values",
-                col, header_name, count
-            );
-        }
+// Counts and profiles of a chunk, plus every flagged cell when `record_findings` is set
+fn check_records(
+    records: &[csv::StringRecord],
+    chunk_number: usize,
+    registry: &CheckRegistry,
+    enable_parallel: bool,
+    record_findings: bool,
+) -> ChunkProcessingResult {
+    // NULL-like values count as missing for profiling when that check is registered
+    let null_check_idx = registry
+        .checks()
+        .iter()
+        .position(|check| check.name() == NullLikeCheck::NAME);
+    let check_count = registry.len() + registry.row_checks().len();
+
+    // Every rayon job fills its own accumulator, they are only combined at the end
+    let new_tallies = || ChunkTallies::new(check_count, record_findings);
+    let mut tallies = if enable_parallel {
+        records
+            .par_iter()
+            .enumerate()
+            .fold(new_tallies, |mut tallies, (row, record)| {
+                process_record(row, record, &mut tallies, registry, null_check_idx);
+                tallies
+            })
+            .reduce(new_tallies, ChunkTallies::merge)
     } else {
-        println!("No NULL-like values found in this chunk");
+        let mut tallies = new_tallies();
+        for (row, record) in records.iter().enumerate() {
+            process_record(row, record, &mut tallies, registry, null_check_idx);
+        }
+        tallies
+    };
+    // Already in order after the reduce, the stable sort only makes sure of it
+    let mut findings = tallies.findings.take().unwrap_or_default();
+    findings.sort_by_key(|finding| finding.row);
+
+    // Keyed by check name, only columns with findings are kept
+    let names = registry
+        .checks()
+        .iter()
+        .map(|check| check.name())
+        .chain(registry.row_checks().iter().map(|check| check.name()));
+    let check_counts = names
+        .zip(tallies.counts)
+        .map(|(name, counts)| {
+            let counts = counts
+                .into_iter()
+                .enumerate()
+                .filter(|(_, count)| *count > 0)
+                .map(|(col, count)| (col, count as usize))
+                .collect();
+            (name.to_string(), counts)
+        })
+        .collect();
+
+    ChunkProcessingResult {
+        chunk_number,
+        rows_processed: records.len(),
+        check_counts,
+        column_profiles: tallies.profiles,
+        row_shape: RowShapeStats::default(),
+        invalid_utf8: InvalidUtf8Stats::default(),
+        findings,
     }
+}
 
-    // Empty values
-    if !result.empty_counts.is_empty() {
-        println!("Empty values:");
-        for (col, count) in result.empty_counts.iter().filter(|(_, &count)| count > 0) {
-            let header_name = if *col < headers.len() {
-                &headers[*col]
-            } else {
-                "Unknown Column"
-            };
+// Counts and profiles for part of a chunk, owned by a single thread
+struct ChunkTallies {
+    counts: Vec<Vec<u64>>, // per check (row checks last), then per column
+    profiles: Vec<ColumnProfile>,
+    findings: Option<Vec<CellFinding>>, // None unless findings are recorded
+}
 
-            println!(
-                "   col_{} column_name={}: {} empty values",
-                col, header_name, count
-            );
+impl ChunkTallies {
+    fn new(check_count: usize, record_findings: bool) -> Self {
+        Self {
+            counts: vec![Vec::new(); check_count],
+            profiles: Vec::new(),
+            findings: record_findings.then(Vec::new),
         }
-    } else {
-        println!("No empty values found in this chunk");
     }
 
-    // White space only values
-    if !result.whitespace_counts.is_empty() {
-        println!("White Space Only values:");
-        for (col, count) in result
-            .whitespace_counts
-            .iter()
-            .filter(|(_, &count)| count > 0)
-        {
-            let header_name = if *col < headers.len() {
-                &headers[*col]
-            } else {
-                "Unknown Column"
-            };
+    fn add(&mut self, row: usize, check_idx: usize, column: usize) {
+        let counts = &mut self.counts[check_idx];
+        if counts.len() <= column {
+            counts.resize(column + 1, 0);
+        }
+        counts[column] += 1;
+        if let Some(findings) = &mut self.findings {
+            findings.push(CellFinding {
+                row,
+                column,
+                check: check_idx,
+            });
+        }
+    }
 
-            println!(
-                "   col_{} column_name={}: {} white space only values",
-                col, header_name, count
-            );
+    fn merge(mut self, other: Self) -> Self {
+        for (counts, other_counts) in self.counts.iter_mut().zip(other.counts) {
+            if counts.len() < other_counts.len() {
+                counts.resize(other_counts.len(), 0);
+            }
+            for (count, other_count) in counts.iter_mut().zip(other_counts) {
+                *count += other_count;
+            }
         }
-    } else {
-        println!("No white space only values found in this chunk");
+        merge_profiles(&mut self.profiles, &other.profiles);
+        if let (Some(findings), Some(other_findings)) = (&mut self.findings, other.findings) {
+            findings.extend(other_findings);
+        }
+        self
     }
 }
 
-type QualityMetrics = (
-    HashMap<usize, usize>, // empty_counts
-    HashMap<usize, usize>, // whitespace_counts
-    HashMap<usize, usize>, // null_counts
-    usize,                 // total_rows
-);
+// Process a single record - the core logic
+fn process_record(
+    row: usize,
+    record: &csv::StringRecord,
+    tallies: &mut ChunkTallies,
+    registry: &CheckRegistry,
+    null_check_idx: Option<usize>,
+) {
+    let checks = registry.checks();
+    if tallies.profiles.len() < record.len() {
+        tallies
+            .profiles
+            .resize_with(record.len(), ColumnProfile::default);
+    }
 
-// Aggregate results function
-pub fn aggregate_results(results: &[ChunkProcessingResult]) -> QualityMetrics {
-    let mut total_null_counts = HashMap::new();
-    let mut total_empty_counts = HashMap::new();
-    let mut total_whitespace_counts = HashMap::new();
-    let mut total_rows = 0;
+    for (i, field) in record.iter().enumerate() {
+        let mut is_missing = field.trim().is_empty();
+        for (check_idx, check) in checks.iter().enumerate() {
+            if check.check_column(i, field) {
+                tallies.add(row, check_idx, i);
+                is_missing |= Some(check_idx) == null_check_idx;
+            }
+        }
 
-    for result in results {
-        total_rows += result.rows_processed;
+        let cell = CellObservation::with_date_window(field, is_missing, registry.date_window());
+        tallies.profiles[i].observe(&cell);
+    }
 
-        for (col, count) in &result.null_counts {
-            *total_null_counts.entry(*col).or_insert(0) += count;
-        }
-        for (col, count) in &result.empty_counts {
-            *total_empty_counts.entry(*col).or_insert(0) += count;
-        }
-        for (col, count) in &result.whitespace_counts {
-            *total_whitespace_counts.entry(*col).or_insert(0) += count;
+    // Row checks are counted against their first column
+    for (row_idx, check) in registry.row_checks().iter().enumerate() {
+        if check.check_row(record, registry.headers()) {
+            tallies.add(row, checks.len() + row_idx, check.column());
         }
     }
+}
 
-    (
-        total_null_counts,
-        total_empty_counts,
-        total_whitespace_counts,
-        total_rows,
-    )
+// Print results function, the output is the one of the console formatter
+pub fn print_chunk_results(
+    result: &ChunkProcessingResult,
+    headers: &[String],
+    registry: &CheckRegistry,
+) {
+    formatter::ConsoleFormatter::new().print_chunk_results(result, headers, registry);
 }
@@ -1,79 +1,628 @@
 mod args;
 
-use args::TrueSightCsvArgs;
+use args::{load_config, Command, DialectArgs, FixArgs, OutputFormat, TrueSightCsvArgs};
 use clap::Parser;
-use std::time::Instant;
-use true_sight_csv::print_chunk_results_spark_style;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use true_sight_csv::print_report_spark_style;
 use true_sight_csv::{
-    prepare_csv_reader, process_csv_chunks, CsvAggregator, CsvChunkIterator, ProcessingConfig,
+    decode_input, evaluate_thresholds, expand_inputs, fix_csv, prepare_csv_reader_from,
+    process_byte_chunks_with_observers, process_csv_chunks_with_observers, process_mapped_chunks,
+    sniff_reader, ByteChunkIterator, CheckRegistry, ChunkObserver, Compression, CsvAggregator,
+    CsvChunkIterator, DatasetJsonReport, Dialect, DuplicateDetector, DuplicateReport, FileMetadata,
+    FindingsWriter, InputEncoding, InputReader, InputSource, JsonReport, LineTerminator, MappedCsv,
+    ProcessingConfig, QuarantineWriter, RawBuffer, RawCapture, ReaderOptions, RecordFixer, RuleSet,
+    SparkStyleFormatter,
 };
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: TrueSightCsvArgs = TrueSightCsvArgs::parse();
-    println!("Provided full path to file: {:?}", &args);
+// Exit codes so CI jobs can tell why a run failed (clap uses 2 for bad arguments)
+const EXIT_ERROR: u8 = 1;
+const EXIT_THRESHOLD_VIOLATION: u8 = 3;
+const EXIT_PARSE_ERROR: u8 = 4;
+const EXIT_IO_ERROR: u8 = 5;
 
-    let validated_path = args.validate_csv_path()?;
-    println!("Valid CSV path: {:?}", validated_path);
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            if let Some(csv::ErrorKind::UnequalLengths { .. }) =
+                e.downcast_ref::<csv::Error>().map(|e| e.kind())
+            {
+                eprintln!("Hint: use --tolerant to report rows with the wrong number of fields instead of stopping");
+            }
+            if let Some(csv::ErrorKind::Utf8 { .. }) =
+                e.downcast_ref::<csv::Error>().map(|e| e.kind())
+            {
+                eprintln!("Hint: use --encoding if the file isn't UTF-8, or --byte-records to report cells that aren't valid UTF-8 instead of stopping");
+            }
+            ExitCode::from(exit_code_for_error(e.as_ref()))
+        }
+    }
+}
 
-    println!("Using chunk size: {}", args.row_chunk_size);
-    println!("Parallel execution: {}", args.is_parallel_enabled(),);
+// Map an error onto the exit code for its category
+fn exit_code_for_error(error: &(dyn Error + 'static)) -> u8 {
+    if let Some(csv_error) = error.downcast_ref::<csv::Error>() {
+        return match csv_error.kind() {
+            csv::ErrorKind::Io(_) => EXIT_IO_ERROR,
+            _ => EXIT_PARSE_ERROR,
+        };
+    }
+    if error.downcast_ref::<std::io::Error>().is_some() {
+        return EXIT_IO_ERROR;
+    }
+    EXIT_ERROR
+}
 
-    let start_time = Instant::now();
+// An input that has been opened, sniffed and had its header row read
+struct OpenedInput {
+    compression: Compression,
+    encoding: InputEncoding,
+    detected_dialect: Dialect,
+    dialect: Dialect,
+    headers: Vec<String>,
+    input: CsvInput,
+    raw: Option<RawBuffer>, // copy of the raw bytes, only kept for quarantine
+}
+
+// Where the records come from
+enum CsvInput {
+    Stream(csv::Reader<InputReader>),
+    Mapped(MappedCsv), // --mmap, parsed in byte ranges
+}
+
+// Everything collected while reading one input
+struct FileRun {
+    metadata: FileMetadata,
+    encoding: InputEncoding,
+    dialect: Dialect,
+    aggregator: CsvAggregator,
+    duplicate_reports: Vec<DuplicateReport>, // shown in the report
+    unique_reports: Vec<DuplicateReport>,    // of the `unique` rules, only these are evaluated
+}
+
+fn open_source(
+    source: &InputSource,
+    args: &TrueSightCsvArgs,
+) -> Result<OpenedInput, Box<dyn Error>> {
+    let (compression, input) = source.open()?;
+
+    // Transcode to UTF-8 first, the dialect can't be sniffed from UTF-16. With
+    // --byte-records the bytes are taken as UTF-8 unless told otherwise, a detected
+    // Windows-1252 would turn every invalid byte into valid text
+    let forced_encoding = args
+        .dialect
+        .encoding
+        .or(args.byte_records.then_some(encoding_rs::UTF_8));
+    let (encoding, input) = decode_input(input, forced_encoding)?;
+
+    // Sniff the dialect, explicit options win over what was detected
+    let (detected_dialect, input) = sniff_reader(input)?;
+    let dialect = args.dialect.apply_dialect_overrides(detected_dialect);
+    let reader_options = ReaderOptions {
+        flexible: args.tolerant,
+        dialect,
+        byte_records: args.byte_records,
+    };
+
+    // The file is mapped and split into byte ranges instead of being streamed, the
+    // sniffed stream is only used for the dialect
+    if args.mmap {
+        if encoding.encoding != encoding_rs::UTF_8 {
+            return Err(format!(
+                "--mmap needs a UTF-8 file, {} is {}",
+                source,
+                encoding.name()
+            )
+            .into());
+        }
+        let mapped = match source {
+            InputSource::File(path) if compression == Compression::None => {
+                MappedCsv::open(path, &reader_options)?
+            }
+            _ => {
+                return Err(format!(
+                    "--mmap needs an uncompressed file on disk, {} can only be streamed",
+                    source
+                )
+                .into())
+            }
+        };
+        let (mapped, raw) = if args.quarantine_dir.is_some() {
+            let (mapped, raw) = mapped.with_raw_capture();
+            (mapped, Some(raw))
+        } else {
+            (mapped, None)
+        };
+        return Ok(OpenedInput {
+            compression,
+            encoding,
+            detected_dialect,
+            dialect,
+            headers: mapped.headers().to_vec(),
+            input: CsvInput::Mapped(mapped),
+            raw,
+        });
+    }
+
+    // Quarantine copies rows byte for byte, so keep what the csv reader sees
+    let (input, raw): (InputReader, _) = if args.quarantine_dir.is_some() {
+        let (capture, raw) = RawCapture::new(input);
+        (Box::new(capture), Some(raw))
+    } else {
+        (input, None)
+    };
+
+    let (headers, reader) = prepare_csv_reader_from(input, &reader_options)?;
+
+    Ok(OpenedInput {
+        compression,
+        encoding,
+        detected_dialect,
+        dialect,
+        headers,
+        input: CsvInput::Stream(reader),
+        raw,
+    })
+}
+
+// Partitions of one dataset must share the exact same header row
+fn check_headers(
+    expected: &[String],
+    first_source: &InputSource,
+    headers: &[String],
+    source: &InputSource,
+) -> Result<(), Box<dyn Error>> {
+    if headers == expected {
+        return Ok(());
+    }
+    Err(format!(
+        "Inconsistent headers: {} has {:?} but {} has {:?}",
+        source, headers, first_source, expected
+    )
+    .into())
+}
+
+// The sniffer only drops the header row on strong evidence, but a wrong guess renames
+// every column, so it is never silent
+fn warn_if_no_header(dialect_args: &DialectArgs, dialect: &Dialect, source: &InputSource) {
+    if !dialect.has_headers && !dialect_args.no_header {
+        eprintln!(
+            "Warning: {} looks like it has no header row, its columns are named col_0, col_1, ... (use --header to read the first row as names)",
+            source
+        );
+    }
+}
 
-    // Get both headers and reader
-    let (found_headers, mut rdr) = prepare_csv_reader(validated_path)?;
-    println!("Found headers: {:?}", found_headers);
+fn process_source(
+    source: &InputSource,
+    opened: OpenedInput,
+    args: &TrueSightCsvArgs,
+    registry: &CheckRegistry,
+    rules: &RuleSet,
+    findings: Option<&mut FindingsWriter>,
+    quarantine: Option<&mut QuarantineWriter>,
+) -> Result<FileRun, Box<dyn Error>> {
+    let OpenedInput {
+        compression,
+        encoding,
+        dialect,
+        headers,
+        input,
+        raw,
+        ..
+    } = opened;
+    warn_if_no_header(&args.dialect, &dialect, source);
 
     // Define chunk size
-    let chunk_size = args.row_chunk_size;
-    let mut aggregator = CsvAggregator::new(found_headers.clone(), chunk_size);
+    let chunk_size = args.chunks.row_chunk_size;
+    let mut aggregator = CsvAggregator::new(headers.clone(), chunk_size, registry);
+
+    // Duplicate detection needs every row in file order, so it runs as an observer.
+    // `unique` rules get a key detector of their own, after the ones of --duplicates
+    let requested_configs = args.duplicate_configs(&headers)?;
+    let requested = requested_configs.len();
+    let mut duplicate_detectors = requested_configs
+        .into_iter()
+        .chain(rules.duplicate_configs(&args.duplicate_base()))
+        .map(|config| DuplicateDetector::new(config, &headers))
+        .collect::<Result<Vec<_>, _>>()?;
 
     let config = ProcessingConfig {
         chunk_size,
-        enable_parallel: args.is_parallel_enabled(),
+        enable_parallel: args.chunks.is_parallel_enabled(),
+        registry: registry.clone(),
+        expected_fields: Some(headers.len()),
+        max_in_flight_chunks: args.max_in_flight_chunks as usize,
+        record_findings: findings.is_some(),
     };
-    let chunk_iterator = CsvChunkIterator::new(rdr.records(), chunk_size);
 
     // Process all chunks
-    let results = process_csv_chunks(chunk_iterator, config)?;
+    let start_time = Instant::now();
+    let mut observers: Vec<&mut dyn ChunkObserver> = duplicate_detectors
+        .iter_mut()
+        .map(|detector| detector as &mut dyn ChunkObserver)
+        .collect();
+    if let Some(findings) = findings {
+        findings.start_source(&source.to_string(), &headers);
+        observers.push(findings);
+    }
+    if let (Some(quarantine), Some(raw)) = (quarantine, raw) {
+        quarantine.start_source(raw, dialect);
+        observers.push(quarantine);
+    }
+    match input {
+        CsvInput::Stream(mut reader) if args.byte_records => {
+            let chunk_iterator = ByteChunkIterator::new(reader.byte_records(), chunk_size);
+            process_byte_chunks_with_observers(
+                chunk_iterator,
+                config,
+                &mut aggregator,
+                &mut observers,
+            )?
+        }
+        CsvInput::Stream(mut reader) => {
+            let chunk_iterator = CsvChunkIterator::new(reader.records(), chunk_size);
+            process_csv_chunks_with_observers(
+                chunk_iterator,
+                config,
+                &mut aggregator,
+                &mut observers,
+            )?
+        }
+        CsvInput::Mapped(mapped) => {
+            process_mapped_chunks(&mapped, config, &mut aggregator, &mut observers)?
+        }
+    }
+    let reports = |detectors: &[DuplicateDetector]| -> Vec<DuplicateReport> {
+        detectors
+            .iter()
+            .filter_map(|detector| detector.report().cloned())
+            .collect()
+    };
+    let (requested_detectors, unique_detectors) = duplicate_detectors.split_at(requested);
+    let mut duplicate_reports = reports(requested_detectors);
+    let unique_reports = reports(unique_detectors);
+    // A `unique` key that --key already reports isn't listed twice
+    let extra_reports: Vec<_> = unique_reports
+        .iter()
+        .filter(|unique| {
+            !duplicate_reports
+                .iter()
+                .any(|report| report.key_columns == unique.key_columns)
+        })
+        .cloned()
+        .collect();
+    duplicate_reports.extend(extra_reports);
 
-    // Extract totals from results
-    let total_rows_processed: usize = results.iter().map(|r| r.rows_processed).sum();
-    let total_chunks_processed = results.len();
+    aggregator.set_processing_time(start_time.elapsed());
 
-    // Calculate data quality totals
-    let total_null_values: usize = results
-        .iter()
-        .map(|r| r.null_counts.values().sum::<usize>())
-        .sum();
-    let total_empty_values: usize = results
-        .iter()
-        .map(|r| r.empty_counts.values().sum::<usize>())
-        .sum();
-    let total_whitespace_values: usize = results
+    Ok(FileRun {
+        metadata: FileMetadata::from_source(source, compression),
+        encoding,
+        dialect,
+        aggregator,
+        duplicate_reports,
+        unique_reports,
+    })
+}
+
+// `fix` subcommand: write a cleaned copy of a single input
+fn run_fix(args: &FixArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let sources = expand_inputs(std::slice::from_ref(&args.input))?;
+    let [source] = sources.as_slice() else {
+        return Err(format!("fix takes a single csv file, {} matched", sources.len()).into());
+    };
+
+    let (_compression, input) = source.open()?;
+    let (_encoding, input) = decode_input(input, args.dialect.encoding)?;
+    let (detected_dialect, input) = sniff_reader(input)?;
+    let dialect = args.dialect.apply_dialect_overrides(detected_dialect);
+    warn_if_no_header(&args.dialect, &dialect, source);
+    let reader_options = ReaderOptions {
+        flexible: false,
+        dialect,
+        byte_records: false,
+    };
+    let (headers, mut reader) = prepare_csv_reader_from(input, &reader_options)?;
+
+    let (_config_path, config) = load_config(args.config.as_ref(), source, &args.nulls)?;
+    let null_check = config.nulls.build(&headers)?;
+    let options = args.fix_options(null_check);
+    let fixer = RecordFixer::new(options.clone(), &headers, dialect.line_terminator);
+    let to_stdout = args.output.as_os_str() == "-";
+    let output: Box<dyn Write> = if to_stdout {
+        Box::new(std::io::stdout().lock())
+    } else {
+        Box::new(File::create(&args.output)?)
+    };
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .terminator(match options.line_terminator {
+            LineTerminator::CrLf => csv::Terminator::CRLF,
+            _ => csv::Terminator::Any(b'\n'),
+        })
+        .from_writer(BufWriter::new(output));
+
+    let chunk_iterator = CsvChunkIterator::new(reader.records(), args.chunks.row_chunk_size);
+    let stats = fix_csv(
+        chunk_iterator,
+        &fixer,
+        dialect.has_headers.then_some(headers.as_slice()),
+        &mut writer,
+        args.chunks.is_parallel_enabled(),
+    )?;
+
+    // The summary can't share stdout with the cleaned data
+    let summary = SparkStyleFormatter::new()
+        .with_max_width(25)
+        .format_fix_summary(&stats);
+    if to_stdout {
+        eprint!("{}", summary);
+    } else {
+        print!("{}", summary);
+        println!("Cleaned file written to {}", args.output.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run() -> Result<ExitCode, Box<dyn Error>> {
+    let args: TrueSightCsvArgs = TrueSightCsvArgs::parse();
+    if let Some(Command::Fix(fix_args)) = &args.command {
+        return run_fix(fix_args);
+    }
+    let table_output = args.format == OutputFormat::Table;
+
+    let sources = args.resolve_inputs()?;
+    if table_output {
+        println!("Provided full path to file: {:?}", &args);
+        for source in &sources {
+            println!("Valid CSV path: {}", source);
+        }
+
+        println!("Using chunk size: {}", args.chunks.row_chunk_size);
+        println!("Parallel execution: {}", args.chunks.is_parallel_enabled(),);
+        if args.mmap {
+            println!("Memory-mapped byte ranges: enabled");
+        } else if args.chunks.is_parallel_enabled() {
+            println!("Max in-flight chunks: {}", args.max_in_flight_chunks);
+        }
+        if args.byte_records {
+            println!("Byte records: enabled");
+        }
+    }
+
+    let (config_path, mut config) = load_config(args.config.as_ref(), &sources[0], &args.nulls)?;
+    args.apply_regex_rules(&mut config.columns)?;
+    args.apply_row_rules(&mut config.rows);
+    if let (true, Some(path)) = (table_output, &config_path) {
+        println!("Using config: {}", path.display());
+    }
+    let threshold_rules = args.threshold_rules();
+    let multiple_inputs = sources.len() > 1;
+
+    // The first input is opened up front, per-column settings are bound to its header row
+    let first_opened = open_source(&sources[0], &args)?;
+    let first_headers = first_opened.headers.clone();
+    let mut first_opened = Some(first_opened);
+
+    // Compare the header rows of the other files before the long scan. Only the header
+    // row is kept and the file is closed again, it is reopened when its turn comes. Stdin
+    // can only be read once, it is checked in its turn
+    for source in sources[1..].iter().filter(|s| **s != InputSource::Stdin) {
+        let headers = open_source(source, &args)?.headers;
+        check_headers(&first_headers, &sources[0], &headers, source)?;
+    }
+
+    let null_check = config.nulls.build(&first_headers)?;
+    let mut rules = RuleSet::compile(&config.columns, &first_headers, &null_check)?;
+    rules.add_row_rules(&config.rows, &first_headers, &null_check)?;
+    let mut registry = CheckRegistry::with_null_check(null_check);
+    registry.set_headers(&first_headers);
+    rules.register(&mut registry)?;
+    if let Some(window) = args.date_window {
+        registry.set_date_window(window);
+    }
+    let registry = registry;
+
+    // Validate thresholds up front so a typo doesn't cost a full scan
+    for rule in &threshold_rules {
+        rule.validate(&first_headers, &registry)?;
+    }
+    if table_output {
+        for check in registry.general_checks() {
+            println!("{} pattern: {}", check.label(), check.show_check_pattern());
+        }
+    }
+
+    // One findings file for all inputs, rows carry the file they came from
+    let mut findings = match args.findings_config() {
+        Some((path, config)) => Some((path, FindingsWriter::create(path, config, &registry)?)),
+        None => None,
+    };
+
+    // Rows of every input end up in the same clean/rejected pair
+    let mut quarantine = match args.quarantine_config() {
+        Some(config) => Some(QuarantineWriter::create(&config, &registry)?),
+        None => None,
+    };
+
+    let start_time = Instant::now();
+    let formatter = SparkStyleFormatter::new().with_max_width(25);
+    let mut runs = Vec::with_capacity(sources.len());
+
+    for (i, source) in sources.iter().enumerate() {
+        let opened = match first_opened.take() {
+            Some(opened) => opened,
+            None => open_source(source, &args)?,
+        };
+        if i > 0 && *source == InputSource::Stdin {
+            check_headers(&first_headers, &sources[0], &opened.headers, source)?;
+        }
+
+        if table_output {
+            if multiple_inputs {
+                println!("\n=== FILE {} of {}: {} ===", i + 1, sources.len(), source);
+            }
+            if opened.compression != Compression::None {
+                println!("Compression: {}", opened.compression.as_str());
+            }
+            if opened.encoding.detected {
+                println!("Detected encoding: {}", opened.encoding);
+            } else {
+                println!("Using encoding: {}", opened.encoding);
+            }
+            println!("Detected dialect: {}", opened.detected_dialect);
+            if opened.dialect != opened.detected_dialect {
+                println!("Using dialect: {}", opened.dialect);
+            }
+            println!("Found headers: {:?}", opened.headers);
+        }
+
+        let file_run = process_source(
+            source,
+            opened,
+            &args,
+            &registry,
+            &rules,
+            findings.as_mut().map(|(_, writer)| writer),
+            quarantine.as_mut(),
+        )?;
+
+        // With several inputs each file gets its own section, the summary is combined
+        if table_output && multiple_inputs {
+            print_report_spark_style(&file_run.aggregator);
+            for duplicate_report in &file_run.duplicate_reports {
+                println!();
+                print!("{}", formatter.format_duplicate_report(duplicate_report));
+            }
+        }
+
+        runs.push(file_run);
+    }
+
+    // Roll all inputs up into one aggregator, thresholds are checked against it
+    let found_headers = runs[0].aggregator.headers().to_vec();
+    let mut combined = CsvAggregator::new(found_headers, args.chunks.row_chunk_size, &registry);
+    for file_run in &runs {
+        combined.merge(file_run.aggregator.clone());
+    }
+    let elapsed_time = start_time.elapsed();
+    combined.set_processing_time(elapsed_time);
+
+    let violations = evaluate_thresholds(&combined, &threshold_rules);
+    let unique_reports: Vec<DuplicateReport> = runs
         .iter()
-        .map(|r| r.whitespace_counts.values().sum::<usize>())
-        .sum();
+        .flat_map(|file_run| file_run.unique_reports.iter().cloned())
+        .collect();
+    let rule_results = rules.evaluate(&combined, &unique_reports);
+    let exit_code = if violations.is_empty() && rule_results.iter().all(|r| r.passed) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_THRESHOLD_VIOLATION)
+    };
+
+    if !table_output {
+        let mut file_reports: Vec<JsonReport> = runs
+            .iter()
+            .map(|file_run| {
+                let mut report =
+                    JsonReport::from_aggregator(&file_run.aggregator, file_run.metadata.clone());
+                report.duplicates = file_run.duplicate_reports.clone();
+                report.dialect = Some(file_run.dialect);
+                report.encoding = Some(file_run.encoding);
+                report.rules = rules.evaluate(&file_run.aggregator, &file_run.unique_reports);
+                report
+            })
+            .collect();
+
+        // A single input keeps the flat report layout
+        if !multiple_inputs {
+            let mut report = file_reports.remove(0);
+            report.threshold_violations = violations;
+            report.rules = rule_results;
+            println!("{}", report.to_json_pretty()?);
+            return Ok(exit_code);
+        }
+
+        let metadata: Vec<FileMetadata> = runs.iter().map(|r| r.metadata.clone()).collect();
+        let mut combined_report =
+            JsonReport::from_aggregator(&combined, FileMetadata::combined(&metadata));
+        combined_report.threshold_violations = violations;
+        combined_report.rules = rule_results;
+        let report = DatasetJsonReport {
+            files: file_reports,
+            combined: combined_report,
+        };
+        println!("{}", report.to_json_pretty()?);
+        return Ok(exit_code);
+    }
 
     // Print Spark-style formatted results
-    print_chunk_results_spark_style(&results, &found_headers);
+    if multiple_inputs {
+        println!("\n=== COMBINED REPORT: {} files ===", runs.len());
+    }
+    print_report_spark_style(&combined);
+    print_summary(&combined, elapsed_time, args.tolerant);
 
-    // Calculate elapsed time
-    let elapsed_time = start_time.elapsed();
+    if !multiple_inputs {
+        for duplicate_report in &runs[0].duplicate_reports {
+            println!();
+            print!("{}", formatter.format_duplicate_report(duplicate_report));
+        }
+    }
 
-    // Set the processing time in the aggregator
-    aggregator.set_processing_time(elapsed_time);
+    if let Some((path, writer)) = &findings {
+        println!(
+            "\nFindings written to {}: {} ({} more over the cap of {} per check and column)",
+            path.display(),
+            writer.written(),
+            writer.dropped(),
+            args.findings_cap
+        );
+    }
 
-    // Add results to aggregator for potential report generation
-    for result in &results {
-        aggregator.add_chunk_results(
-            &result.null_counts,
-            &result.empty_counts,
-            &result.whitespace_counts,
-            result.rows_processed,
+    if let Some(quarantine) = &quarantine {
+        println!(
+            "\nQuarantine: {} clean and {} rejected rows written to {}",
+            quarantine.clean_rows(),
+            quarantine.rejected_rows(),
+            quarantine.dir().display()
         );
     }
 
+    if !rules.is_empty() {
+        println!();
+        print!("{}", formatter.format_rule_results(&rule_results));
+    }
+
+    if !threshold_rules.is_empty() {
+        println!();
+        print!("{}", formatter.format_threshold_violations(&violations));
+    }
+
+    Ok(exit_code)
+}
+
+// Processing totals and the overall quality numbers
+fn print_summary(aggregator: &CsvAggregator, elapsed_time: Duration, tolerant: bool) {
+    let total_rows_processed = aggregator.total_rows();
+    let total_chunks_processed = aggregator.total_chunks();
+    let found_headers = aggregator.headers();
+
+    // Calculate data quality totals per check
+    let check_totals: Vec<(&str, usize)> = aggregator
+        .registry()
+        .general_checks()
+        .map(|check| (check.label(), aggregator.total_for(check.name())))
+        .collect();
+    let total_issues: usize = check_totals.iter().map(|(_, total)| total).sum();
+
     // Final summary with correct totals
     println!("\n=== PROCESSING COMPLETE ===");
     println!("Total rows processed: {}", total_rows_processed);
@@ -84,14 +633,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== CSV QUALITY REPORT ===");
     println!("Total rows processed: {}", total_rows_processed);
     println!("Total columns: {}", found_headers.len());
+    let row_shape = aggregator.row_shape();
+    if tolerant {
+        println!(
+            "Ragged rows: {} ({} short, {} long)",
+            row_shape.ragged_rows(),
+            row_shape.short_rows,
+            row_shape.long_rows
+        );
+    }
+    let invalid_utf8 = aggregator.invalid_utf8().invalid_cells();
+    if invalid_utf8 > 0 {
+        println!("Invalid UTF-8 cells: {}", invalid_utf8);
+    }
     println!("Total data quality issues found:");
-    println!("  - NULL-like values: {}", total_null_values);
-    println!("  - Empty values: {}", total_empty_values);
-    println!("  - Whitespace-only values: {}", total_whitespace_values);
-    println!(
-        "  - Total issues: {}",
-        total_null_values + total_empty_values + total_whitespace_values
-    );
+    for (label, total) in &check_totals {
+        println!("  - {} values: {}", label, total);
+    }
+    println!("  - Total issues: {}", total_issues);
 
     // Performance metrics
     let rows_per_second = if elapsed_time.as_secs() > 0 {
@@ -101,11 +660,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     println!("Processing rate: {:.0} rows/second", rows_per_second);
 
-    // Data quality percentages
+    // Data quality percentages. A cell can be flagged by more than one check (e.g. a
+    // custom check on top of the built in ones), so the issues can outnumber the cells
     let total_cells = total_rows_processed * found_headers.len();
-    let total_issues = total_null_values + total_empty_values + total_whitespace_values;
     let quality_percentage = if total_cells > 0 {
-        ((total_cells - total_issues) as f64 / total_cells as f64) * 100.0
+        (total_cells.saturating_sub(total_issues) as f64 / total_cells as f64) * 100.0
     } else {
         100.0
     };
@@ -113,6 +672,4 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         "Overall data quality: {:.2}% clean cells",
         quality_percentage
     );
-
-    Ok(())
 }
@@ -0,0 +1,387 @@
+use crate::quarantine::RawBuffer;
+use crate::utf8::{decode_records, InvalidUtf8Stats};
+use crate::{
+    check_chunk, csv_reader_builder, header_names, log_chunk_read, observe_checked_chunk,
+    wants_findings, ChunkObserver, CsvAggregator, LineTerminator, ProcessingConfig, ReaderOptions,
+};
+use csv::Position;
+use memchr::{memchr2, memchr3};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::error::Error;
+use std::fs::File;
+use std::io::{Cursor, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+// A run of whole records in the mapped file, `start` is where the csv reader would
+// be after the record before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordRange {
+    pub bytes: Range<usize>,
+    pub start: Position,
+    pub records: usize,
+}
+
+// Splits the bytes after the header into ranges of `records_per_range` records.
+// Only quotes at the start of a field open a quoted field, the same as the csv
+// parser, so newlines and delimiters inside quotes never end a range.
+pub struct RangeSplitter<'a> {
+    data: &'a [u8],
+    delimiter: u8,
+    quote: u8,
+    cr_only: bool, // `\r` ends records, `\n` is an ordinary byte the parser doesn't count as a line
+    pos: Position,
+    records_per_range: usize,
+}
+
+impl<'a> RangeSplitter<'a> {
+    pub fn new(
+        data: &'a [u8],
+        start: Position,
+        options: &ReaderOptions,
+        records_per_range: usize,
+    ) -> Self {
+        let dialect = &options.dialect;
+        Self {
+            data,
+            delimiter: dialect.delimiter,
+            quote: dialect.quote,
+            cr_only: dialect.line_terminator == LineTerminator::Cr,
+            pos: start,
+            records_per_range: records_per_range.max(1),
+        }
+    }
+
+    // Skip to just past the closing quote, `""` is an escaped quote
+    fn skip_quoted(&self, mut i: usize, line: &mut u64) -> usize {
+        let data = self.data;
+        while let Some(offset) = memchr2(self.quote, b'\n', &data[i..]) {
+            let at = i + offset;
+            if data[at] == b'\n' {
+                *line += !self.cr_only as u64;
+                i = at + 1;
+            } else if data.get(at + 1) == Some(&self.quote) {
+                i = at + 2;
+            } else {
+                return at + 1;
+            }
+        }
+        data.len()
+    }
+}
+
+impl Iterator for RangeSplitter<'_> {
+    type Item = RecordRange;
+
+    fn next(&mut self) -> Option<RecordRange> {
+        let data = self.data;
+        let start = self.pos.byte() as usize;
+        let mut line = self.pos.line();
+        let mut records = 0;
+        let mut record_start = start;
+        let mut i = start;
+
+        while records < self.records_per_range {
+            let Some(offset) = memchr3(self.quote, b'\r', b'\n', &data[i..]) else {
+                // The last record doesn't need a line ending
+                if record_start < data.len() {
+                    records += 1;
+                }
+                i = data.len();
+                break;
+            };
+            let at = i + offset;
+            let byte = data[at];
+            i = at + 1;
+            if byte == self.quote {
+                if at == record_start || data[at - 1] == self.delimiter {
+                    i = self.skip_quoted(i, &mut line);
+                }
+                continue;
+            }
+            if byte == b'\n' {
+                if self.cr_only {
+                    continue;
+                }
+                line += 1;
+            }
+            // Blank lines are skipped by the parser and aren't records
+            if at > record_start {
+                records += 1;
+            }
+            record_start = i;
+        }
+
+        if records == 0 {
+            self.pos.set_byte(data.len() as u64);
+            return None;
+        }
+        let range = RecordRange {
+            bytes: start..i,
+            start: self.pos.clone(),
+            records,
+        };
+        self.pos.set_byte(i as u64);
+        self.pos.set_line(line);
+        self.pos.set_record(self.pos.record() + records as u64);
+        Some(range)
+    }
+}
+
+// A csv file mapped into memory, so byte ranges of it can be parsed on separate
+// threads. Only plain files on disk can be mapped.
+pub struct MappedCsv {
+    mmap: Mmap,
+    options: ReaderOptions,
+    headers: Vec<String>,
+    data_start: Position, // first record after the header row
+    raw: Option<RawBuffer>,
+}
+
+impl MappedCsv {
+    pub fn open(path: &Path, options: &ReaderOptions) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        // SAFETY: the file is only read, it must not be truncated while it is checked
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let mut rdr = csv_reader_builder(options).from_reader(&mmap[..]);
+        let headers = header_names(&mut rdr, options)?;
+        let data_start = if options.dialect.has_headers {
+            rdr.position().clone()
+        } else {
+            Position::new()
+        };
+        drop(rdr);
+
+        Ok(Self {
+            mmap,
+            options: options.clone(),
+            headers,
+            data_start,
+            raw: None,
+        })
+    }
+
+    // Keep the raw bytes of every row for quarantine, like RawCapture does for streams
+    pub fn with_raw_capture(mut self) -> (Self, RawBuffer) {
+        let raw = RawBuffer::default();
+        self.raw = Some(raw.clone());
+        (self, raw)
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    pub fn ranges(&self, records_per_range: usize) -> RangeSplitter<'_> {
+        RangeSplitter::new(
+            &self.mmap,
+            self.data_start.clone(),
+            &self.options,
+            records_per_range,
+        )
+    }
+
+    // Parse one range. The reader reads the first row before seeking, so rows with a
+    // different field count are rejected against the same row as in a full read
+    pub fn read_range(
+        &self,
+        range: &RecordRange,
+    ) -> Result<(Vec<csv::StringRecord>, InvalidUtf8Stats), Box<dyn Error + Send + Sync>> {
+        let input = Cursor::new(&self.mmap[..range.bytes.end]);
+        let mut rdr = csv_reader_builder(&self.options).from_reader(input);
+        rdr.seek_raw(
+            SeekFrom::Start(range.bytes.start as u64),
+            range.start.clone(),
+        )?;
+        let (records, invalid_utf8) = if self.options.byte_records {
+            decode_records(rdr.byte_records().collect::<Result<Vec<_>, _>>()?)
+        } else {
+            let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+            (records, InvalidUtf8Stats::default())
+        };
+        if records.len() != range.records {
+            return Err(format!(
+                "Byte range {:?} parsed into {} records instead of {}",
+                range.bytes,
+                records.len(),
+                range.records
+            )
+            .into());
+        }
+        Ok((records, invalid_utf8))
+    }
+}
+
+// Like process_csv_chunks_with_observers, but every chunk is a byte range of the
+// mapped file that is parsed and checked on its own thread. As many ranges as there
+// are threads are in memory at once, observers still see them in file order.
+pub fn process_mapped_chunks(
+    mapped: &MappedCsv,
+    mut config: ProcessingConfig,
+    aggregator: &mut CsvAggregator,
+    observers: &mut [&mut dyn ChunkObserver],
+) -> Result<(), Box<dyn Error>> {
+    config.record_findings |= wants_findings(observers);
+    let batch_size = if config.enable_parallel {
+        rayon::current_num_threads()
+    } else {
+        1
+    };
+    let mut ranges = mapped.ranges(config.chunk_size);
+    let mut chunk_number = 0;
+    let mut raw_end = 0;
+
+    loop {
+        let batch: Vec<RecordRange> = ranges.by_ref().take(batch_size).collect();
+        if batch.is_empty() {
+            break;
+        }
+
+        let first_chunk = chunk_number + 1;
+        let chunks = batch
+            .par_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let chunk = mapped.read_range(range)?;
+                let (records, result) = check_chunk(chunk, first_chunk + i, &config, false)
+                    .map_err(|e| e.to_string())?;
+                Ok((range.bytes.end, records, result))
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error + Send + Sync>>>()
+            .map_err(|e| e as Box<dyn Error>)?;
+
+        for (end, records, result) in chunks {
+            log_chunk_read(records.len());
+            if let Some(raw) = &mapped.raw {
+                raw.append(&mapped.mmap[raw_end..end], false);
+                raw_end = end;
+            }
+            observe_checked_chunk(observers, &records, &result)?;
+            aggregator.add_chunk_results(&result);
+            chunk_number += 1;
+        }
+    }
+
+    // Whatever follows the last record (blank lines) belongs to it
+    if let Some(raw) = &mapped.raw {
+        raw.append(&mapped.mmap[raw_end..], true);
+    }
+    for observer in observers.iter_mut() {
+        observer.finish()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prepare_csv_reader_from, Dialect};
+
+    fn map_file(name: &str, data: &str, options: &ReaderOptions) -> MappedCsv {
+        let path = std::env::temp_dir().join(format!(
+            "true-sight-mapped-{}-{}.csv",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, data).unwrap();
+        let mapped = MappedCsv::open(&path, options).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        mapped
+    }
+
+    // Records (with positions) of a plain sequential read
+    fn read_all(data: &str, options: &ReaderOptions) -> (Vec<String>, Vec<csv::StringRecord>) {
+        let input = Box::new(Cursor::new(data.as_bytes().to_vec()));
+        let (headers, mut rdr) = prepare_csv_reader_from(input, options).unwrap();
+        (headers, rdr.records().map(|r| r.unwrap()).collect())
+    }
+
+    fn assert_same_records(name: &str, data: &str, options: &ReaderOptions) {
+        let (headers, expected) = read_all(data, options);
+        let mapped = map_file(name, data, options);
+        assert_eq!(mapped.headers(), headers.as_slice());
+
+        for records_per_range in 1..=expected.len() + 1 {
+            let mut records = Vec::new();
+            for range in mapped.ranges(records_per_range) {
+                assert!(range.records <= records_per_range);
+                records.extend(mapped.read_range(&range).unwrap().0);
+            }
+            assert_eq!(
+                records.len(),
+                expected.len(),
+                "{} per range",
+                records_per_range
+            );
+            for (record, expected) in records.iter().zip(&expected) {
+                assert_eq!(record, expected);
+                assert_eq!(record.position(), expected.position());
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges_match_sequential_read() {
+        let options = ReaderOptions::default();
+        let data = "id,note,qty\n\
+                    1,\"multi\nline, with comma\",5\n\
+                    2,\"escaped \"\"quote\"\"\",6\n\
+                    \n\
+                    3,stray\"quote,7\n\
+                    4,\"\",8\n\
+                    5,last,9";
+        assert_same_records("lf", data, &options);
+
+        let crlf = "id,note\r\n1,\"a\r\nb\"\r\n\r\n2,c\r\n3,\"d\"\"\"\r\n";
+        assert_same_records("crlf", crlf, &options);
+
+        let options = ReaderOptions {
+            dialect: Dialect {
+                delimiter: b';',
+                has_headers: false,
+                line_terminator: LineTerminator::Cr,
+                ..Dialect::default()
+            },
+            ..ReaderOptions::default()
+        };
+        assert_same_records(
+            "cr",
+            "1;'x\ry';2\r3;z;4\r",
+            &ReaderOptions {
+                dialect: Dialect {
+                    quote: b'\'',
+                    ..options.dialect
+                },
+                ..options
+            },
+        );
+    }
+
+    #[test]
+    fn test_range_sizes_and_ragged_rows() {
+        let data = "a,b\n1,2\n3,4\n5,6\n7,8\n9,10\n";
+        let mapped = map_file("sizes", data, &ReaderOptions::default());
+        let ranges: Vec<_> = mapped.ranges(2).collect();
+        assert_eq!(
+            ranges.iter().map(|r| r.records).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(ranges[0].bytes, 4..12);
+        assert_eq!(ranges[2].bytes.end, data.len());
+        assert_eq!(ranges[2].start.line(), 6);
+        assert_eq!(ranges[2].start.record(), 5);
+
+        // A short row in a later range is still measured against the header
+        let ragged = map_file("ragged", "a,b\n1,2\n3\n", &ReaderOptions::default());
+        let ranges: Vec<_> = ragged.ranges(1).collect();
+        assert!(ragged.read_range(&ranges[0]).is_ok());
+        let error = ragged.read_range(&ranges[1]).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<csv::Error>().map(|e| e.kind()),
+            Some(csv::ErrorKind::UnequalLengths { .. })
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::NullLikeCheck;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+// A list of values treated as NULL, compared against the trimmed cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullVocabulary {
+    values: Vec<String>,
+    case_sensitive: bool,
+}
+
+impl NullVocabulary {
+    pub fn new<I, S>(values: I, case_sensitive: bool) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            values: values.into_iter().map(Into::into).collect(),
+            case_sensitive,
+        }
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        let trimmed = value.trim(); // Borrowed slice, no allocation
+        if self.case_sensitive {
+            self.values.iter().any(|null| trimmed == null)
+        } else {
+            self.values
+                .iter()
+                .any(|null| trimmed.eq_ignore_ascii_case(null))
+        }
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    // e.g. `NULL, N/A, NA` or `\N, null (case-sensitive)`
+    pub fn describe(&self) -> String {
+        let list = self.values.join(", ");
+        if self.case_sensitive {
+            format!("{} (case-sensitive)", list)
+        } else {
+            list
+        }
+    }
+}
+
+// NULL-like settings from the `[nulls]` table of the config file and the command line
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NullConfig {
+    pub values: Option<Vec<String>>, // replaces NULL_LIKE_VALUES
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub columns: BTreeMap<String, Vec<String>>, // column name (or index) -> its own list
+}
+
+impl NullConfig {
+    // Settings from `other` win, column lists are combined
+    pub fn merge(&mut self, other: NullConfig) {
+        if other.values.is_some() {
+            self.values = other.values;
+        }
+        self.case_sensitive |= other.case_sensitive;
+        self.columns.extend(other.columns);
+    }
+
+    // Bind the column lists to the header row
+    pub fn build(&self, headers: &[String]) -> Result<NullLikeCheck, Box<dyn Error>> {
+        let mut check = match &self.values {
+            Some(values) => NullLikeCheck::with_values(values.clone(), self.case_sensitive),
+            None => {
+                NullLikeCheck::with_values(NullLikeCheck::NULL_LIKE_VALUES, self.case_sensitive)
+            }
+        };
+        for (column, values) in &self.columns {
+            let index = resolve_column(column, headers)?;
+            check = check.with_column_values(index, &headers[index], values.clone());
+        }
+        Ok(check)
+    }
+}
+
+// A column by name, or by index when no header has that name
+pub fn resolve_column(column: &str, headers: &[String]) -> Result<usize, Box<dyn Error>> {
+    if let Some(index) = headers.iter().position(|h| h == column) {
+        return Ok(index);
+    }
+    match column.parse::<usize>() {
+        Ok(index) if index < headers.len() => Ok(index),
+        _ => Err(format!("Unknown column '{}', headers are {:?}", column, headers).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PatternCheck;
+
+    fn headers() -> Vec<String> {
+        ["id", "email", "shipped"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        let config = NullConfig {
+            values: Some(vec![
+                "\\N".into(),
+                "(null)".into(),
+                "-".into(),
+                "1900-01-01".into(),
+            ]),
+            ..NullConfig::default()
+        };
+        let check = config.build(&headers()).unwrap();
+        assert!(check.check(" \\N "));
+        assert!(check.check("(NULL)"));
+        assert!(check.check("1900-01-01"));
+        assert!(!check.check("NULL"));
+        assert_eq!(check.show_check_pattern(), "\\N, (null), -, 1900-01-01");
+    }
+
+    #[test]
+    fn test_case_sensitive_and_column_overrides() {
+        let mut config = NullConfig {
+            case_sensitive: true,
+            ..NullConfig::default()
+        };
+        config.merge(NullConfig {
+            columns: BTreeMap::from([("2".to_string(), vec!["?".to_string()])]),
+            ..NullConfig::default()
+        });
+        let check = config.build(&headers()).unwrap();
+
+        assert!(check.check_column(0, "NULL"));
+        assert!(!check.check_column(0, "null"));
+        assert!(check.check_column(2, "?"));
+        assert!(!check.check_column(2, "NULL"));
+        assert_eq!(
+            check.show_check_pattern(),
+            "NULL, N/A, NA, NONE, NaN (case-sensitive); shipped: ?"
+        );
+
+        let unknown = NullConfig {
+            columns: BTreeMap::from([("missing".to_string(), vec![])]),
+            ..NullConfig::default()
+        };
+        assert!(unknown.build(&headers()).is_err());
+    }
+}
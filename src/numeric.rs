@@ -0,0 +1,327 @@
+use std::f64::consts::PI;
+
+// Values are buffered and folded into the centroids in batches
+const DEFAULT_COMPRESSION: f64 = 100.0;
+const BUFFER_FACTOR: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+// Merging t-digest (Dunning) for approximate quantiles.
+// Two digests merge into one with the same accuracy guarantees, in any order.
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(value);
+        if self.buffer.len() >= self.compression as usize * BUFFER_FACTOR {
+            self.flush();
+        }
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.count() == 0.0 {
+            return;
+        }
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        let mut centroids = std::mem::take(&mut self.centroids);
+        centroids.extend_from_slice(&other.centroids);
+        centroids.extend(
+            other
+                .buffer
+                .iter()
+                .map(|&mean| Centroid { mean, weight: 1.0 }),
+        );
+        self.centroids = self.compress(centroids);
+    }
+
+    pub fn count(&self) -> f64 {
+        self.centroids.iter().map(|c| c.weight).sum::<f64>() + self.buffer.len() as f64
+    }
+
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut centroids = std::mem::take(&mut self.centroids);
+        centroids.extend(
+            self.buffer
+                .drain(..)
+                .map(|mean| Centroid { mean, weight: 1.0 }),
+        );
+        self.centroids = self.compress(centroids);
+    }
+
+    // k1 scale function, keeps centroids small near the tails
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn k_inverse(&self, k: f64) -> f64 {
+        ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    fn compress(&self, mut centroids: Vec<Centroid>) -> Vec<Centroid> {
+        if centroids.len() <= 1 {
+            return centroids;
+        }
+        centroids.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+
+        let mut compressed = Vec::with_capacity(self.compression as usize);
+        let mut iter = centroids.into_iter();
+        let mut current = iter.next().unwrap();
+        let mut weight_so_far = 0.0;
+        let mut q_limit = self.k_inverse(self.k(0.0) + 1.0);
+
+        for next in iter {
+            let q = (weight_so_far + current.weight + next.weight) / total;
+            if q <= q_limit {
+                let weight = current.weight + next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                compressed.push(current);
+                q_limit = self.k_inverse(self.k(weight_so_far / total) + 1.0);
+                current = next;
+            }
+        }
+        compressed.push(current);
+        compressed
+    }
+
+    // Approximate value at quantile q (0.0..=1.0)
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let centroids = if self.buffer.is_empty() {
+            self.centroids.clone()
+        } else {
+            let mut digest = self.clone();
+            digest.flush();
+            digest.centroids
+        };
+
+        let first = centroids.first()?;
+        let last = centroids.last()?;
+        let q = q.clamp(0.0, 1.0);
+        if centroids.len() == 1 {
+            return Some(first.mean);
+        }
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q * total;
+
+        // Tails interpolate towards the exact min and max
+        if target < first.weight / 2.0 {
+            let fraction = target / (first.weight / 2.0);
+            return Some(self.min + (first.mean - self.min) * fraction);
+        }
+        if target > total - last.weight / 2.0 {
+            let fraction = (target - (total - last.weight / 2.0)) / (last.weight / 2.0);
+            return Some(last.mean + (self.max - last.mean) * fraction);
+        }
+
+        let mut cumulative = 0.0;
+        for pair in centroids.windows(2) {
+            let left_center = cumulative + pair[0].weight / 2.0;
+            let right_center = cumulative + pair[0].weight + pair[1].weight / 2.0;
+            if target <= right_center {
+                let fraction = (target - left_center) / (right_center - left_center);
+                return Some(pair[0].mean + (pair[1].mean - pair[0].mean) * fraction);
+            }
+            cumulative += pair[0].weight;
+        }
+        Some(last.mean)
+    }
+}
+
+// Mergeable numeric summary for a column: Welford moments, min/max and a t-digest.
+// Count, min, max, mean and variance combine exactly (Chan et al.), quantiles are approximate.
+#[derive(Debug, Clone)]
+pub struct NumericStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    digest: TDigest,
+}
+
+impl Default for NumericStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            digest: TDigest::default(),
+        }
+    }
+}
+
+impl NumericStats {
+    pub fn observe(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.digest.add(value);
+    }
+
+    pub fn merge(&mut self, other: &NumericStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.digest.merge(&other.digest);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    // Sample standard deviation
+    pub fn stddev(&self) -> Option<f64> {
+        (self.count > 1).then(|| (self.m2 / (self.count - 1) as f64).sqrt())
+    }
+
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.digest.quantile(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {} to be within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_moments() {
+        let mut stats = NumericStats::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.observe(value);
+        }
+        assert_eq!(stats.count(), 8);
+        assert_eq!(stats.min(), Some(2.0));
+        assert_eq!(stats.max(), Some(9.0));
+        assert_close(stats.mean().unwrap(), 5.0, 1e-12);
+        assert_close(stats.stddev().unwrap(), (32.0f64 / 7.0).sqrt(), 1e-12);
+
+        stats.observe(f64::INFINITY); // ignored
+        assert_eq!(stats.count(), 8);
+
+        assert_eq!(NumericStats::default().mean(), None);
+    }
+
+    #[test]
+    fn test_merge_matches_single_pass() {
+        let values: Vec<f64> = (0..10_000).map(|i| ((i * 7919) % 10_000) as f64).collect();
+
+        let mut whole = NumericStats::default();
+        values.iter().for_each(|v| whole.observe(*v));
+
+        // Split unevenly and merge in a different order
+        let mut parts: Vec<NumericStats> = values
+            .chunks(3_333)
+            .map(|chunk| {
+                let mut stats = NumericStats::default();
+                chunk.iter().for_each(|v| stats.observe(*v));
+                stats
+            })
+            .collect();
+        parts.reverse();
+        let mut merged = NumericStats::default();
+        parts.iter().for_each(|part| merged.merge(part));
+
+        assert_eq!(merged.count(), whole.count());
+        assert_eq!(merged.min(), whole.min());
+        assert_eq!(merged.max(), whole.max());
+        assert_close(merged.mean().unwrap(), whole.mean().unwrap(), 1e-9);
+        assert_close(merged.stddev().unwrap(), whole.stddev().unwrap(), 1e-9);
+
+        for q in [0.25, 0.5, 0.75, 0.95] {
+            assert_close(merged.quantile(q).unwrap(), q * 10_000.0, 100.0);
+            assert_close(whole.quantile(q).unwrap(), q * 10_000.0, 100.0);
+        }
+    }
+
+    #[test]
+    fn test_small_digest() {
+        let mut digest = TDigest::default();
+        assert_eq!(digest.quantile(0.5), None);
+        digest.add(42.0);
+        assert_eq!(digest.quantile(0.5), Some(42.0));
+        digest.add(1.0);
+        digest.add(100.0);
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(1.0), Some(100.0));
+        assert_eq!(digest.quantile(0.5), Some(42.0));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::dates::{observe_date, DateObservation, DateProfile, DateWindow};
+use crate::inference::{classify_value, InferredType, TypeCounts};
+use crate::numeric::NumericStats;
+use crate::sketch::{hash_value, HeavyHitters, HyperLogLog};
+
+// The expensive part of profiling a cell, done before any shared state is touched
+#[derive(Debug, Clone)]
+pub struct CellObservation<'a> {
+    pub value: &'a str,
+    pub value_type: Option<InferredType>, // None for missing cells (blank or NULL-like)
+    pub numeric: Option<f64>,
+    pub date: Option<DateObservation>, // values shaped like a date, even impossible ones
+    pub hash: u64,
+}
+
+impl<'a> CellObservation<'a> {
+    pub fn new(value: &'a str, is_missing: bool) -> Self {
+        Self::with_date_window(value, is_missing, &DateWindow::default())
+    }
+
+    pub fn with_date_window(value: &'a str, is_missing: bool, window: &DateWindow) -> Self {
+        let value_type = if is_missing {
+            None
+        } else {
+            Some(classify_value(value))
+        };
+        let numeric = match value_type {
+            Some(InferredType::Integer | InferredType::Decimal) => value.trim().parse().ok(),
+            _ => None,
+        };
+        let date = match value_type {
+            Some(InferredType::Integer | InferredType::Decimal | InferredType::Boolean) | None => {
+                None
+            }
+            _ => observe_date(value, window),
+        };
+        Self {
+            value,
+            value_type,
+            numeric,
+            date,
+            hash: hash_value(value),
+        }
+    }
+}
+
+// Everything we learn about a column beyond the pattern check counts.
+// Profiles from different chunks can be merged in any order.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnProfile {
+    pub types: TypeCounts,
+    pub numeric: NumericStats,
+    pub distinct: HyperLogLog,    // approximate distinct non-missing values
+    pub top_values: HeavyHitters, // most frequent non-missing values
+    pub dates: DateProfile,
+}
+
+impl ColumnProfile {
+    pub fn observe(&mut self, cell: &CellObservation) {
+        match cell.value_type {
+            Some(value_type) => {
+                self.types.observe(value_type);
+                self.distinct.insert_hash(cell.hash);
+                self.top_values.insert(cell.value);
+            }
+            None => self.types.observe_missing(),
+        }
+        if let Some(value) = cell.numeric {
+            self.numeric.observe(value);
+        }
+        if let Some(date) = &cell.date {
+            self.dates.observe(date);
+        }
+    }
+
+    pub fn merge(&mut self, other: &ColumnProfile) {
+        self.types.merge(&other.types);
+        self.numeric.merge(&other.numeric);
+        self.distinct.merge(&other.distinct);
+        self.top_values.merge(&other.top_values);
+        self.dates.merge(&other.dates);
+    }
+
+    // Numeric stats are only meaningful when the column itself is numeric
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self.types.inferred(),
+            Some(InferredType::Integer | InferredType::Decimal)
+        )
+    }
+}
+
+// Merge per-column profiles, growing the target if the other side saw more columns
+pub fn merge_profiles(target: &mut Vec<ColumnProfile>, other: &[ColumnProfile]) {
+    if target.len() < other.len() {
+        target.resize_with(other.len(), ColumnProfile::default);
+    }
+    for (profile, other_profile) in target.iter_mut().zip(other) {
+        profile.merge(other_profile);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_and_merge_profiles() {
+        let mut first = vec![ColumnProfile::default()];
+        first[0].observe(&CellObservation::new("5", false));
+        first[0].observe(&CellObservation::new("NULL", true));
+
+        let mut second = vec![ColumnProfile::default(), ColumnProfile::default()];
+        second[0].observe(&CellObservation::new("oops", false));
+        second[1].observe(&CellObservation::new("2024-01-15", false));
+
+        merge_profiles(&mut first, &second);
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].types.inferred(), Some(InferredType::Integer));
+        assert_eq!(first[0].types.mismatch_count(), 1);
+        assert_eq!(first[0].types.missing(), 1);
+        assert_eq!(first[1].types.inferred(), Some(InferredType::Date));
+        assert_eq!(first[1].dates.total(), 1);
+        assert!(first[0].dates.is_empty());
+
+        assert!(first[0].is_numeric());
+        assert!(!first[1].is_numeric());
+        assert_eq!(first[0].numeric.count(), 1);
+        assert_eq!(first[0].numeric.max(), Some(5.0));
+
+        // Missing values don't count towards cardinality
+        assert_eq!(first[0].distinct.estimate(), 2);
+        assert_eq!(first[0].top_values.total(), 2);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::{CellFinding, CheckRegistry, ChunkObserver, Dialect};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const CLEAN_FILE: &str = "clean.csv";
+pub const REJECTED_FILE: &str = "rejected.csv";
+pub const REASONS_COLUMN: &str = "_ts_reasons";
+
+// Separates the check names in the reasons column
+const REASON_SEPARATOR: &str = ";";
+
+// Bytes read through a RawCapture that haven't been claimed by a row yet
+#[derive(Debug, Default)]
+struct RawState {
+    start: u64, // stream offset of bytes[0]
+    bytes: Vec<u8>,
+    eof: bool,
+}
+
+// Shared handle on the raw bytes seen by the csv reader, so rows can be copied out
+// exactly as they were written (quoting, spacing and line endings included)
+#[derive(Debug, Clone, Default)]
+pub struct RawBuffer {
+    state: Arc<Mutex<RawState>>,
+}
+
+impl RawBuffer {
+    // Feed bytes that didn't come through a RawCapture, e.g. from a memory map
+    pub(crate) fn append(&self, bytes: &[u8], eof: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.bytes.extend_from_slice(bytes);
+        state.eof |= eof;
+    }
+
+    // Remove and return everything before stream offset `end`, plus any line ending
+    // right after it. The csv reader can put the start of the next record on the `\n`
+    // of a `\r\n`, that byte still belongs to the row before.
+    fn take_until(&self, end: u64) -> Vec<u8> {
+        let mut state = self.state.lock().unwrap();
+        let mut len = (end.saturating_sub(state.start) as usize).min(state.bytes.len());
+        while matches!(state.bytes.get(len), Some(b'\r' | b'\n')) {
+            len += 1;
+        }
+        state.start += len as u64;
+        state.bytes.drain(..len).collect()
+    }
+
+    // Remove and return everything left, only valid once the reader hit the end
+    fn take_rest(&self) -> Vec<u8> {
+        let mut state = self.state.lock().unwrap();
+        debug_assert!(state.eof, "raw bytes taken before the end of the input");
+        state.start += state.bytes.len() as u64;
+        std::mem::take(&mut state.bytes)
+    }
+}
+
+// Read adapter that keeps a copy of every byte passing through it. It sits between
+// the (decompressed, sniffed) input and the csv reader so offsets match
+// StringRecord::position()
+pub struct RawCapture<R> {
+    inner: R,
+    buffer: RawBuffer,
+}
+
+impl<R: Read> RawCapture<R> {
+    pub fn new(inner: R) -> (Self, RawBuffer) {
+        let buffer = RawBuffer::default();
+        let capture = Self {
+            inner,
+            buffer: buffer.clone(),
+        };
+        (capture, buffer)
+    }
+}
+
+impl<R: Read> Read for RawCapture<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let mut state = self.buffer.state.lock().unwrap();
+        if n == 0 && !buf.is_empty() {
+            state.eof = true;
+        }
+        state.bytes.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuarantineConfig {
+    pub dir: PathBuf,
+    pub reject_on: Vec<String>, // check names, empty means every registered check
+}
+
+// Streams every row to clean.csv or rejected.csv in input order. A row is rejected
+// when any cell fails one of the rejecting checks, the rejected file gets an extra
+// `_ts_reasons` column with the names of those checks. The flagged cells come from
+// the workers that checked the chunk, nothing is checked again here.
+pub struct QuarantineWriter {
+    clean: BufWriter<File>,
+    rejected: BufWriter<File>,
+    reason_of: Vec<Option<usize>>, // per registry check index (row checks last), into `reasons`
+    reasons: Vec<String>,          // names of the rejecting checks, in reporting order
+    dir: PathBuf,
+    raw: RawBuffer,
+    dialect: Dialect,
+    header_pending: bool, // the current input's header bytes are still in the buffer
+    header_written: bool, // only the first input's header row is copied
+    pending: Option<Vec<String>>, // reasons of the last row seen, waiting for its end offset
+    clean_rows: usize,
+    rejected_rows: usize,
+}
+
+impl QuarantineWriter {
+    pub fn create(
+        config: &QuarantineConfig,
+        registry: &CheckRegistry,
+    ) -> Result<Self, Box<dyn Error>> {
+        // Check names by index, the way CellFinding::check counts them
+        let names: Vec<&str> = registry
+            .checks()
+            .iter()
+            .map(|check| check.name())
+            .chain(registry.row_checks().iter().map(|check| check.name()))
+            .collect();
+        let rejecting: Vec<usize> = if config.reject_on.is_empty() {
+            (0..names.len()).collect()
+        } else {
+            let mut rejecting = Vec::new();
+            for name in &config.reject_on {
+                match names.iter().position(|check| check == name) {
+                    Some(idx) if !rejecting.contains(&idx) => rejecting.push(idx),
+                    Some(_) => {}
+                    None => return Err(format!("Unknown check for --reject-on: '{}'", name).into()),
+                }
+            }
+            rejecting
+        };
+        let mut reason_of = vec![None; names.len()];
+        for (reason, &idx) in rejecting.iter().enumerate() {
+            reason_of[idx] = Some(reason);
+        }
+        let reasons = rejecting
+            .iter()
+            .map(|&idx| names[idx].to_string())
+            .collect();
+
+        fs::create_dir_all(&config.dir)?;
+        let open = |name: &str| -> io::Result<BufWriter<File>> {
+            Ok(BufWriter::new(File::create(config.dir.join(name))?))
+        };
+
+        Ok(Self {
+            clean: open(CLEAN_FILE)?,
+            rejected: open(REJECTED_FILE)?,
+            reason_of,
+            reasons,
+            dir: config.dir.clone(),
+            raw: RawBuffer::default(),
+            dialect: Dialect::default(),
+            header_pending: false,
+            header_written: false,
+            pending: None,
+            clean_rows: 0,
+            rejected_rows: 0,
+        })
+    }
+
+    // Call before each input with the buffer of the RawCapture its reader was built on
+    pub fn start_source(&mut self, raw: RawBuffer, dialect: Dialect) {
+        self.raw = raw;
+        self.dialect = dialect;
+        self.header_pending = dialect.has_headers;
+        self.pending = None;
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn clean_rows(&self) -> usize {
+        self.clean_rows
+    }
+
+    pub fn rejected_rows(&self) -> usize {
+        self.rejected_rows
+    }
+
+    fn write_header(&mut self, raw: &[u8]) -> io::Result<()> {
+        if self.header_written || raw.is_empty() {
+            return Ok(());
+        }
+        self.header_written = true;
+        self.clean.write_all(raw)?;
+        let reasons_header = [REASONS_COLUMN.to_string()];
+        self.write_with_reasons(raw, &reasons_header)
+    }
+
+    fn write_row(&mut self, raw: &[u8], reasons: &[String]) -> io::Result<()> {
+        if reasons.is_empty() {
+            self.clean_rows += 1;
+            let (line, terminator) = split_terminator(raw);
+            self.clean.write_all(line)?;
+            self.clean
+                .write_all(terminator.unwrap_or(self.default_terminator()))
+        } else {
+            self.rejected_rows += 1;
+            self.write_with_reasons(raw, reasons)
+        }
+    }
+
+    // The row as it was read plus one more field before its line ending
+    fn write_with_reasons(&mut self, raw: &[u8], reasons: &[String]) -> io::Result<()> {
+        let (line, terminator) = split_terminator(raw);
+        let terminator = terminator.unwrap_or(self.default_terminator());
+        let field = quote_field(&reasons.join(REASON_SEPARATOR), &self.dialect);
+
+        self.rejected.write_all(line)?;
+        self.rejected.write_all(&[self.dialect.delimiter])?;
+        self.rejected.write_all(field.as_bytes())?;
+        self.rejected.write_all(terminator)
+    }
+
+    // Used when the last row of an input has no line ending, so the next input's rows
+    // don't run into it
+    fn default_terminator(&self) -> &'static [u8] {
+        match self.dialect.line_terminator {
+            crate::LineTerminator::Lf => b"\n",
+            crate::LineTerminator::CrLf => b"\r\n",
+            crate::LineTerminator::Cr => b"\r",
+        }
+    }
+
+    // A row's raw bytes run up to the start of the next row
+    fn flush_pending(&mut self, end: Option<u64>) -> io::Result<()> {
+        if let Some(reasons) = self.pending.take() {
+            let raw = match end {
+                Some(end) => self.raw.take_until(end),
+                None => self.raw.take_rest(),
+            };
+            self.write_row(&raw, &reasons)?;
+        }
+        Ok(())
+    }
+}
+
+impl ChunkObserver for QuarantineWriter {
+    // Rows are written from observe_findings, which gets the records as well
+    fn observe_chunk(&mut self, _records: &[csv::StringRecord]) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn wants_findings(&self) -> bool {
+        true
+    }
+
+    fn observe_findings(
+        &mut self,
+        records: &[csv::StringRecord],
+        findings: &[CellFinding],
+    ) -> Result<(), Box<dyn Error>> {
+        // Rejecting checks of every row, a check counts once however many cells it flagged
+        let mut row_reasons = vec![Vec::new(); records.len()];
+        for finding in findings {
+            if let Some(reason) = self.reason_of[finding.check] {
+                row_reasons[finding.row].push(reason);
+            }
+        }
+
+        for (record, mut reasons) in records.iter().zip(row_reasons) {
+            let start = record
+                .position()
+                .map(|pos| pos.byte())
+                .ok_or("Quarantine needs record positions")?;
+
+            if self.header_pending {
+                self.header_pending = false;
+                let header = self.raw.take_until(start);
+                self.write_header(&header)?;
+            }
+            self.flush_pending(Some(start))?;
+            reasons.sort_unstable();
+            reasons.dedup();
+            self.pending = Some(
+                reasons
+                    .into_iter()
+                    .map(|reason| self.reasons[reason].clone())
+                    .collect(),
+            );
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.header_pending {
+            // A file with a header row and nothing else
+            self.header_pending = false;
+            let header = self.raw.take_rest();
+            self.write_header(&header)?;
+        }
+        self.flush_pending(None)?;
+        self.clean.flush()?;
+        self.rejected.flush()?;
+        Ok(())
+    }
+}
+
+// Split off a trailing \n, \r\n or \r
+fn split_terminator(raw: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let content_end = raw
+        .iter()
+        .rposition(|&b| b != b'\n' && b != b'\r')
+        .map_or(0, |i| i + 1);
+    let (line, terminator) = raw.split_at(content_end);
+    (line, (!terminator.is_empty()).then_some(terminator))
+}
+
+// Quote the reasons field the way the csv writer would
+fn quote_field(value: &str, dialect: &Dialect) -> String {
+    let quote = dialect.quote as char;
+    let needs_quotes = value
+        .chars()
+        .any(|c| c == dialect.delimiter as char || c == quote || c == '\n' || c == '\r');
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let doubled = format!("{}{}", quote, quote);
+    format!("{}{}{}", quote, value.replace(quote, &doubled), quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prepare_csv_reader_from, EmptyCheck, NullLikeCheck, ReaderOptions};
+
+    fn quarantine(data: &str, reject_on: Vec<String>) -> (String, String, usize, usize) {
+        let dir = std::env::temp_dir().join(format!(
+            "true-sight-quarantine-{}-{}",
+            std::process::id(),
+            reject_on.len()
+        ));
+        let config = QuarantineConfig {
+            dir: dir.clone(),
+            reject_on,
+        };
+        let registry = CheckRegistry::new();
+        let mut writer = QuarantineWriter::create(&config, &registry).unwrap();
+
+        let (capture, raw) = RawCapture::new(io::Cursor::new(data.as_bytes().to_vec()));
+        let options = ReaderOptions {
+            dialect: crate::sniff::sniff(data.as_bytes(), true),
+            ..ReaderOptions::default()
+        };
+        let (_headers, mut reader) = prepare_csv_reader_from(Box::new(capture), &options).unwrap();
+        writer.start_source(raw, options.dialect);
+
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        for chunk in [&records[..1], &records[1..]] {
+            let result = crate::check_records(chunk, 1, &registry, false, true);
+            crate::observe_checked_chunk(&mut [&mut writer], chunk, &result).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let clean = fs::read_to_string(dir.join(CLEAN_FILE)).unwrap();
+        let rejected = fs::read_to_string(dir.join(REJECTED_FILE)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        (clean, rejected, writer.clean_rows(), writer.rejected_rows())
+    }
+
+    #[test]
+    fn test_split_keeps_order_and_quoting() {
+        let data = "id,name,email\r\n1,\"Smith, J\",a@b.com\r\n2,Doe,NULL\r\n3,\"x\"\"y\",\r\n4,Roe,c@d.com";
+        let (clean, rejected, clean_rows, rejected_rows) = quarantine(data, Vec::new());
+
+        assert_eq!(clean_rows, 2);
+        assert_eq!(rejected_rows, 2);
+        assert_eq!(
+            clean,
+            "id,name,email\r\n1,\"Smith, J\",a@b.com\r\n4,Roe,c@d.com\r\n"
+        );
+        assert_eq!(
+            rejected,
+            format!(
+                "id,name,email,_ts_reasons\r\n2,Doe,NULL,{}\r\n3,\"x\"\"y\",,{}\r\n",
+                NullLikeCheck::NAME,
+                EmptyCheck::NAME
+            )
+        );
+    }
+
+    #[test]
+    fn test_only_configured_checks_reject() {
+        let data = "id,email\n1,NULL\n2,\n";
+        let (clean, rejected, _, _) = quarantine(data, vec![EmptyCheck::NAME.to_string()]);
+        assert_eq!(clean, "id,email\n1,NULL\n");
+        assert_eq!(
+            rejected,
+            format!("id,email,_ts_reasons\n2,,{}\n", EmptyCheck::NAME)
+        );
+    }
+
+    #[test]
+    fn test_quote_field() {
+        let dialect = Dialect {
+            delimiter: b';',
+            ..Dialect::default()
+        };
+        assert_eq!(quote_field("A;B", &dialect), "\"A;B\"");
+        assert_eq!(quote_field("A", &dialect), "A");
+    }
+}
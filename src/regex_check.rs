@@ -0,0 +1,145 @@
+use crate::{NullLikeCheck, PatternCheck};
+use regex::Regex;
+use std::error::Error;
+
+// Named patterns usable as `@name` instead of a regex
+pub const REGEX_PRESETS: [(&str, &str); 6] = [
+    ("email", r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}$"),
+    ("zip", r"^\d{5}(-\d{4})?$"), // US ZIP and ZIP+4
+    (
+        "uuid",
+        r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+    ),
+    (
+        "iso8601",
+        r"^\d{4}-\d{2}-\d{2}([T ]\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?)?$",
+    ),
+    ("phone", r"^\+?[0-9][0-9 ().-]{5,18}[0-9]$"),
+    ("sku", r"^[A-Z]{2,5}-?\d{2,10}$"),
+];
+
+// `@email` becomes the preset's regex, anything else is used as is
+pub fn resolve_pattern(spec: &str) -> Result<&str, Box<dyn Error>> {
+    let Some(preset) = spec.strip_prefix('@') else {
+        return Ok(spec);
+    };
+    REGEX_PRESETS
+        .iter()
+        .find(|(name, _)| *name == preset)
+        .map(|(_, pattern)| *pattern)
+        .ok_or_else(|| {
+            let names: Vec<String> = REGEX_PRESETS
+                .iter()
+                .map(|(name, _)| format!("@{}", name))
+                .collect();
+            format!(
+                "Unknown regex preset '{}', presets are {}",
+                spec,
+                names.join(", ")
+            )
+            .into()
+        })
+}
+
+// Counts the values of one column that don't match a pattern. Empty and NULL-like
+// values are skipped, the other checks already count those
+pub struct RegexCheck {
+    name: String,
+    column: usize,
+    regex: Regex,
+    pattern: String,
+    null_check: NullLikeCheck,
+}
+
+impl RegexCheck {
+    pub fn new(
+        name: &str,
+        column: usize,
+        spec: &str,
+        null_check: NullLikeCheck,
+    ) -> Result<Self, Box<dyn Error>> {
+        let regex = Regex::new(resolve_pattern(spec)?)
+            .map_err(|e| format!("Invalid regex for '{}': {}", name, e))?;
+        let pattern = if spec.starts_with('@') {
+            format!("{} ({})", spec, regex.as_str())
+        } else {
+            spec.to_string()
+        };
+        Ok(Self {
+            name: name.to_string(),
+            column,
+            regex,
+            pattern,
+            null_check,
+        })
+    }
+
+    fn violates(&self, value: &str) -> bool {
+        !value.trim().is_empty()
+            && !self.null_check.check_column(self.column, value)
+            && !self.regex.is_match(value)
+    }
+}
+
+impl PatternCheck for RegexCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check(&self, value: &str) -> bool {
+        self.violates(value)
+    }
+
+    fn check_column(&self, column: usize, value: &str) -> bool {
+        column == self.column && self.violates(value)
+    }
+
+    fn show_check_pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    fn column(&self) -> Option<usize> {
+        Some(self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(preset: &str, value: &str) -> bool {
+        let check = RegexCheck::new("test", 0, preset, NullLikeCheck::new()).unwrap();
+        !check.check(value)
+    }
+
+    #[test]
+    fn test_presets() {
+        assert!(matches("@email", "john.doe@email.com"));
+        assert!(!matches("@email", "john.doe@"));
+        assert!(matches("@zip", "94105"));
+        assert!(matches("@zip", "94105-1234"));
+        assert!(!matches("@zip", "9410"));
+        assert!(matches("@uuid", "123e4567-e89b-12d3-a456-426614174000"));
+        assert!(!matches("@uuid", "123e4567"));
+        assert!(matches("@iso8601", "2024-01-15T08:30:00Z"));
+        assert!(matches("@iso8601", "2024-01-15"));
+        assert!(!matches("@iso8601", "1/15/2024"));
+        assert!(matches("@phone", "+1 (415) 555-0100"));
+        assert!(!matches("@phone", "call me"));
+        assert!(matches("@sku", "SKU123"));
+        assert!(!matches("@sku", "123"));
+    }
+
+    #[test]
+    fn test_custom_pattern_and_missing_values() {
+        let check = RegexCheck::new("zip", 5, r"^\d{5}$", NullLikeCheck::new()).unwrap();
+        assert!(check.check_column(5, "ABCDE"));
+        assert!(!check.check_column(4, "ABCDE"));
+        assert!(!check.check_column(5, ""));
+        assert!(!check.check_column(5, "NULL"));
+        assert_eq!(check.show_check_pattern(), r"^\d{5}$");
+
+        assert!(RegexCheck::new("bad", 0, "(", NullLikeCheck::new()).is_err());
+        assert!(RegexCheck::new("bad", 0, "@nope", NullLikeCheck::new()).is_err());
+    }
+}
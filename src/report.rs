@@ -0,0 +1,400 @@
+use crate::{
+    ColumnProfile, Compression, CsvAggregator, DateProfile, Dialect, DuplicateReport, InferredType,
+    InputEncoding, InputSource, InvalidUtf8Stats, NumericStats, RowShapeStats, RuleResult,
+    ThresholdViolation,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+// Machine readable report, built from a finished CsvAggregator
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonReport {
+    pub file: FileMetadata,
+    pub encoding: Option<InputEncoding>, // detected or given encoding and BOM, set by the caller
+    pub dialect: Option<Dialect>,        // how the file was parsed, set by the caller
+    pub headers: Vec<String>,
+    pub total_rows: usize,
+    pub total_columns: usize,
+    pub total_cells: usize,
+    pub total_chunks: usize,
+    pub chunk_size: usize,
+    pub processing_time_ms: Option<f64>,
+    pub date_window: String, // dates outside it count as implausible
+    pub checks: Vec<CheckSummary>,
+    pub columns: Vec<ColumnReport>,
+    pub structure: RowShapeStats, // ragged rows, only populated in tolerant mode
+    pub invalid_utf8: InvalidUtf8Stats, // only populated with --byte-records
+    pub threshold_violations: Vec<ThresholdViolation>,
+    pub duplicates: Vec<DuplicateReport>,
+    pub rules: Vec<RuleResult>, // pass/fail of the rules file, set by the caller
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMetadata {
+    pub path: String,
+    pub size_bytes: Option<u64>, // on disk, so compressed size for compressed files
+    pub compression: Option<Compression>,
+}
+
+impl FileMetadata {
+    pub fn from_path(path: &Path) -> Self {
+        Self {
+            path: path.display().to_string(),
+            size_bytes: std::fs::metadata(path).ok().map(|m| m.len()),
+            compression: Compression::detect(path).ok(),
+        }
+    }
+
+    pub fn from_source(source: &InputSource, compression: Compression) -> Self {
+        match source {
+            InputSource::File(path) => Self {
+                compression: Some(compression),
+                ..Self::from_path(path)
+            },
+            InputSource::Stdin => Self {
+                path: source.to_string(),
+                size_bytes: None,
+                compression: Some(compression),
+            },
+        }
+    }
+
+    // Stand-in for the roll-up of several inputs
+    pub fn combined(files: &[FileMetadata]) -> Self {
+        Self {
+            path: format!("<{} files>", files.len()),
+            size_bytes: files.iter().map(|file| file.size_bytes).sum(),
+            compression: None,
+        }
+    }
+}
+
+// Totals for a single check across the whole file
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckSummary {
+    pub name: String,
+    pub label: String,
+    pub pattern: String,
+    pub count: usize,
+    pub percent_of_cells: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnReport {
+    pub index: usize,
+    pub name: String,
+    pub checks: BTreeMap<String, CheckCount>, // keyed by PatternCheck::name()
+    pub types: TypeReport,
+    pub numeric: Option<NumericReport>, // only for integer and decimal columns
+    pub cardinality: CardinalityReport,
+    pub dates: Option<DateReport>, // only for columns with date-like values
+}
+
+// Number of most frequent values included per column
+const TOP_VALUES_REPORTED: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CardinalityReport {
+    pub non_missing_count: u64,
+    pub approx_distinct: u64,      // HyperLogLog estimate
+    pub top_values: Vec<TopValue>, // Misra-Gries, counts are lower bounds
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopValue {
+    pub value: String,
+    pub count: u64,
+    pub percent_of_non_missing: f64,
+}
+
+impl CardinalityReport {
+    fn from_profile(profile: &ColumnProfile) -> Self {
+        let non_missing = profile.types.non_missing();
+        Self {
+            non_missing_count: non_missing,
+            approx_distinct: profile.distinct.estimate().min(non_missing),
+            top_values: profile
+                .top_values
+                .top(TOP_VALUES_REPORTED)
+                .into_iter()
+                .map(|(value, count)| TopValue {
+                    value: value.to_string(),
+                    count,
+                    percent_of_non_missing: percent(count as usize, non_missing as usize),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DateReport {
+    pub count: u64,
+    pub formats: Vec<DateFormatCount>, // most common first
+    pub mixed_formats: bool,
+    pub impossible_count: u64,
+    pub with_timezone_count: u64,
+    pub without_timezone_count: u64,
+    pub mixed_timezones: bool,
+    pub outside_window_count: u64,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DateFormatCount {
+    pub format: String,
+    pub count: u64,
+}
+
+impl DateReport {
+    fn from_profile(dates: &DateProfile) -> Self {
+        Self {
+            count: dates.total(),
+            formats: dates
+                .formats()
+                .into_iter()
+                .map(|(format, count)| DateFormatCount { format, count })
+                .collect(),
+            mixed_formats: dates.mixed_formats(),
+            impossible_count: dates.impossible(),
+            with_timezone_count: dates.with_zone(),
+            without_timezone_count: dates.without_zone(),
+            mixed_timezones: dates.mixed_zones(),
+            outside_window_count: dates.outside_window(),
+            min: dates.min().map(|date| date.to_string()),
+            max: dates.max().map(|date| date.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NumericReport {
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub p25: Option<f64>,
+    pub median: Option<f64>,
+    pub p75: Option<f64>,
+    pub p95: Option<f64>,
+}
+
+impl NumericReport {
+    fn from_stats(stats: &NumericStats) -> Self {
+        Self {
+            count: stats.count(),
+            min: stats.min(),
+            max: stats.max(),
+            mean: stats.mean(),
+            stddev: stats.stddev(),
+            p25: stats.quantile(0.25),
+            median: stats.quantile(0.5),
+            p75: stats.quantile(0.75),
+            p95: stats.quantile(0.95),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeReport {
+    pub inferred: Option<InferredType>,
+    pub mismatch_count: u64,
+    pub mismatch_percent_of_column: f64,
+    pub missing_count: u64,
+    pub counts: BTreeMap<&'static str, u64>, // non-missing values per detected type
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckCount {
+    pub count: usize,
+    pub percent_of_column: f64,
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total > 0 {
+        (count as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+impl JsonReport {
+    pub fn from_aggregator(aggregator: &CsvAggregator, file: FileMetadata) -> Self {
+        let total_rows = aggregator.total_rows();
+        let total_cells = total_rows * aggregator.headers().len();
+
+        let checks = aggregator
+            .registry()
+            .general_checks()
+            .map(|check| {
+                let count = aggregator.total_for(check.name());
+                CheckSummary {
+                    name: check.name().to_string(),
+                    label: check.label().to_string(),
+                    pattern: check.show_check_pattern().to_string(),
+                    count,
+                    percent_of_cells: percent(count, total_cells),
+                }
+            })
+            .collect();
+
+        let columns = aggregator
+            .headers()
+            .iter()
+            .zip(aggregator.column_stats())
+            .enumerate()
+            .map(|(index, (header, stats))| {
+                let checks = aggregator
+                    .registry()
+                    .general_checks()
+                    .map(|check| {
+                        let count = stats.count_for(check.name());
+                        (
+                            check.name().to_string(),
+                            CheckCount {
+                                count,
+                                percent_of_column: percent(count, total_rows),
+                            },
+                        )
+                    })
+                    .collect();
+
+                let profile = stats.profile();
+                let types = &profile.types;
+                let mismatch_count = types.mismatch_count();
+
+                ColumnReport {
+                    index,
+                    name: header.clone(),
+                    checks,
+                    types: TypeReport {
+                        inferred: types.inferred(),
+                        mismatch_count,
+                        mismatch_percent_of_column: percent(mismatch_count as usize, total_rows),
+                        missing_count: types.missing(),
+                        counts: InferredType::ALL
+                            .iter()
+                            .filter(|value_type| types.count(**value_type) > 0)
+                            .map(|value_type| (value_type.as_str(), types.count(*value_type)))
+                            .collect(),
+                    },
+                    numeric: profile
+                        .is_numeric()
+                        .then(|| NumericReport::from_stats(&profile.numeric)),
+                    cardinality: CardinalityReport::from_profile(profile),
+                    dates: (!profile.dates.is_empty())
+                        .then(|| DateReport::from_profile(&profile.dates)),
+                }
+            })
+            .collect();
+
+        JsonReport {
+            file,
+            encoding: None,
+            dialect: None,
+            headers: aggregator.headers().to_vec(),
+            total_rows,
+            total_columns: aggregator.headers().len(),
+            total_cells,
+            total_chunks: aggregator.total_chunks(),
+            chunk_size: aggregator.chunk_size(),
+            processing_time_ms: aggregator
+                .processing_time()
+                .map(|duration| duration.as_secs_f64() * 1000.0),
+            date_window: aggregator.registry().date_window().to_string(),
+            checks,
+            columns,
+            structure: aggregator.row_shape().clone(),
+            invalid_utf8: aggregator.invalid_utf8().clone(),
+            threshold_violations: Vec::new(),
+            duplicates: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+// Report for several inputs, thresholds are checked against the combined roll-up
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetJsonReport {
+    pub files: Vec<JsonReport>,
+    pub combined: JsonReport,
+}
+
+impl DatasetJsonReport {
+    pub fn to_json_pretty(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckRegistry, ChunkProcessingResult, EmptyCheck, NullLikeCheck};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_report_from_aggregator() {
+        let registry = CheckRegistry::new();
+        let headers = vec!["id".to_string(), "email".to_string()];
+        let mut aggregator = CsvAggregator::new(headers, 10, &registry);
+
+        let result = ChunkProcessingResult {
+            chunk_number: 1,
+            rows_processed: 4,
+            check_counts: [
+                (EmptyCheck::NAME.to_string(), [(1, 2)].into_iter().collect()),
+                (NullLikeCheck::NAME.to_string(), HashMap::new()),
+            ]
+            .into_iter()
+            .collect(),
+            column_profiles: Vec::new(),
+            row_shape: RowShapeStats::default(),
+            invalid_utf8: InvalidUtf8Stats::default(),
+            findings: Vec::new(),
+        };
+        aggregator.add_chunk_results(&result);
+        aggregator.add_chunk_results(&result);
+        aggregator.set_processing_time(Duration::from_millis(1500));
+
+        let file = FileMetadata {
+            path: "data.csv".to_string(),
+            size_bytes: Some(42),
+            compression: None,
+        };
+        let report = JsonReport::from_aggregator(&aggregator, file);
+
+        assert_eq!(report.total_rows, 8);
+        assert_eq!(report.total_chunks, 2);
+        assert_eq!(report.total_cells, 16);
+        assert_eq!(report.processing_time_ms, Some(1500.0));
+        assert_eq!(report.columns[1].checks[EmptyCheck::NAME].count, 4);
+        assert_eq!(
+            report.columns[1].checks[EmptyCheck::NAME].percent_of_column,
+            50.0
+        );
+        assert_eq!(report.columns[0].checks[EmptyCheck::NAME].count, 0);
+
+        let empty_summary = report
+            .checks
+            .iter()
+            .find(|c| c.name == EmptyCheck::NAME)
+            .unwrap();
+        assert_eq!(empty_summary.count, 4);
+        assert_eq!(empty_summary.percent_of_cells, 25.0);
+
+        // Round trip through serde_json to make sure it is valid JSON
+        let json = report.to_json_pretty().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["file"]["path"], "data.csv");
+        assert_eq!(value["columns"][1]["name"], "email");
+    }
+}
@@ -0,0 +1,631 @@
+use crate::inference::{parse_date, parse_datetime};
+use crate::nulls::resolve_column;
+use crate::{HeaderLookup, NullLikeCheck, PatternCheck, RowCheck};
+use chrono::NaiveDateTime;
+use std::cmp::Ordering;
+use std::error::Error;
+
+// Rules over several fields of a row, e.g.
+//
+//   last_updated_timestamp >= order_date
+//   quantity * unit_price >= 0
+//   if empty(email) then present(phone)
+//
+// Columns are referenced by name, or in backticks when the name has spaces or is an
+// index. Comparisons with a missing value (blank or NULL-like) are unknown and don't
+// count as violations, use empty() and present() for those.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Column(String), // `quoted column`
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+const OPERATORS: [&str; 14] = [
+    "==", "!=", "<>", "<=", ">=", "&&", "||", "=", "<", ">", "+", "-", "*", "/",
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = if c.is_ascii_digit() || c == '.' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| format!("Invalid number '{}'", &rest[..len]))?;
+            (Token::Number(number), len)
+        } else if c == '\'' || c == '"' || c == '`' {
+            let end = rest[1..]
+                .find(c)
+                .ok_or_else(|| format!("Missing closing {} in '{}'", c, rest))?;
+            let value = rest[1..=end].to_string();
+            let token = if c == '`' {
+                Token::Column(value)
+            } else {
+                Token::Text(value)
+            };
+            (token, end + 2)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (Token::Ident(rest[..len].to_string()), len)
+        } else if c == '(' {
+            (Token::LParen, 1)
+        } else if c == ')' {
+            (Token::RParen, 1)
+        } else if c == '!' && !rest.starts_with("!=") {
+            (Token::Op("!"), 1)
+        } else {
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| format!("Unexpected '{}'", c))?;
+            (Token::Op(op), op.len())
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Column(usize),
+    Empty(usize),
+    Present(usize),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Implies(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    // Whether the expression yields true or false, a rule has to
+    fn is_boolean(&self) -> bool {
+        matches!(
+            self,
+            Expr::Bool(_)
+                | Expr::Empty(_)
+                | Expr::Present(_)
+                | Expr::Not(_)
+                | Expr::And(..)
+                | Expr::Or(..)
+                | Expr::Implies(..)
+                | Expr::Compare(..)
+        )
+    }
+
+    fn columns(&self, columns: &mut Vec<usize>) {
+        match self {
+            Expr::Column(column) | Expr::Empty(column) | Expr::Present(column) => {
+                if !columns.contains(column) {
+                    columns.push(*column);
+                }
+            }
+            Expr::Neg(inner) | Expr::Not(inner) => inner.columns(columns),
+            Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Implies(a, b)
+            | Expr::Compare(_, a, b)
+            | Expr::Arith(_, a, b) => {
+                a.columns(columns);
+                b.columns(columns);
+            }
+            Expr::Number(_) | Expr::Text(_) | Expr::Bool(_) => {}
+        }
+    }
+}
+
+// Recursive descent over the tokens, lowest precedence first
+struct Parser<'h> {
+    tokens: Vec<Token>,
+    position: usize,
+    headers: &'h HeaderLookup,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    // Consumes the next token if it is one of the operators or keywords
+    fn accept(&mut self, ops: &[&str]) -> bool {
+        let found = match self.peek() {
+            Some(Token::Op(op)) => ops.contains(op),
+            Some(Token::Ident(word)) => ops.iter().any(|op| word.eq_ignore_ascii_case(op)),
+            _ => false,
+        };
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        match self.next() {
+            Some(next) if next == token => Ok(()),
+            next => Err(format!("Expected {:?}, found {:?}", token, next)),
+        }
+    }
+
+    fn column(&self, name: &str) -> Result<usize, String> {
+        resolve_column(name, self.headers.headers()).map_err(|e| e.to_string())
+    }
+
+    fn rule(&mut self) -> Result<Expr, String> {
+        if self.accept(&["if"]) {
+            let condition = self.or()?;
+            if !self.accept(&["then"]) {
+                return Err("Expected 'then' after the 'if' condition".to_string());
+            }
+            let consequence = self.or()?;
+            return Ok(Expr::Implies(Box::new(condition), Box::new(consequence)));
+        }
+        self.or()
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.accept(&["or", "||"]) {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.accept(&["and", "&&"]) {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.accept(&["not", "!"]) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.sum()?;
+        let op = match self.peek() {
+            Some(Token::Op("=" | "==")) => CompareOp::Eq,
+            Some(Token::Op("!=" | "<>")) => CompareOp::Ne,
+            Some(Token::Op("<")) => CompareOp::Lt,
+            Some(Token::Op("<=")) => CompareOp::Le,
+            Some(Token::Op(">")) => CompareOp::Gt,
+            Some(Token::Op(">=")) => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.sum()?;
+        Ok(Expr::Compare(op, Box::new(left), Box::new(right)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut left = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op("+")) => ArithOp::Add,
+                Some(Token::Op("-")) => ArithOp::Sub,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expr::Arith(op, Box::new(left), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op("*")) => ArithOp::Mul,
+                Some(Token::Op("/")) => ArithOp::Div,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expr::Arith(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.accept(&["-"]) {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Text(text)) => Ok(Expr::Text(text)),
+            Some(Token::Column(name)) => Ok(Expr::Column(self.column(&name)?)),
+            Some(Token::LParen) => {
+                let expr = self.rule()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(word)) if word.eq_ignore_ascii_case("true") => Ok(Expr::Bool(true)),
+            Some(Token::Ident(word)) if word.eq_ignore_ascii_case("false") => Ok(Expr::Bool(false)),
+            Some(Token::Ident(word)) if self.peek() == Some(&Token::LParen) => {
+                self.position += 1;
+                let column = match self.next() {
+                    Some(Token::Ident(name) | Token::Column(name)) => self.column(&name)?,
+                    other => {
+                        return Err(format!(
+                            "Expected a column in {}(), found {:?}",
+                            word, other
+                        ))
+                    }
+                };
+                self.expect(Token::RParen)?;
+                match word.to_ascii_lowercase().as_str() {
+                    "empty" => Ok(Expr::Empty(column)),
+                    "present" => Ok(Expr::Present(column)),
+                    _ => Err(format!(
+                        "Unknown function '{}', use empty() or present()",
+                        word
+                    )),
+                }
+            }
+            Some(Token::Ident(name)) => Ok(Expr::Column(self.column(&name)?)),
+            other => Err(format!("Unexpected {:?}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value<'a> {
+    Missing,
+    Bool(bool),
+    Number(f64),
+    Text(&'a str), // fields and string literals, typed when they are compared
+}
+
+// Raised when values can't be combined, e.g. `'abc' * 2`, and counted as a violation
+struct Mismatch;
+
+fn as_number(value: &str) -> Option<f64> {
+    value.trim().parse().ok()
+}
+
+fn as_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    parse_date(value)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .or_else(|| parse_datetime(value))
+}
+
+// Numbers compare as numbers, dates and timestamps as dates, anything else as text
+fn compare_values(left: Value, right: Value) -> Result<Option<Ordering>, Mismatch> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => Ok(a.partial_cmp(&b)),
+        (Value::Number(a), Value::Text(b)) => Ok(a.partial_cmp(&as_number(b).ok_or(Mismatch)?)),
+        (Value::Text(a), Value::Number(b)) => Ok(as_number(a).ok_or(Mismatch)?.partial_cmp(&b)),
+        (Value::Text(a), Value::Text(b)) => {
+            if let (Some(a), Some(b)) = (as_number(a), as_number(b)) {
+                return Ok(a.partial_cmp(&b));
+            }
+            if let (Some(a), Some(b)) = (as_datetime(a), as_datetime(b)) {
+                return Ok(Some(a.cmp(&b)));
+            }
+            Ok(Some(a.cmp(b)))
+        }
+        (Value::Bool(a), Value::Bool(b)) => Ok(Some(a.cmp(&b))),
+        _ => Err(Mismatch),
+    }
+}
+
+fn number(value: Value) -> Result<Option<f64>, Mismatch> {
+    match value {
+        Value::Missing => Ok(None),
+        Value::Number(number) => Ok(Some(number)),
+        Value::Text(text) => as_number(text).map(Some).ok_or(Mismatch),
+        Value::Bool(_) => Err(Mismatch),
+    }
+}
+
+fn boolean(value: Value) -> Result<Option<bool>, Mismatch> {
+    match value {
+        Value::Missing => Ok(None),
+        Value::Bool(value) => Ok(Some(value)),
+        _ => Err(Mismatch),
+    }
+}
+
+fn from_option(value: Option<bool>) -> Value<'static> {
+    value.map_or(Value::Missing, Value::Bool)
+}
+
+// A row rule compiled against a header row
+pub struct RowRule {
+    name: String,
+    rule: String,
+    expr: Expr,
+    columns: Vec<usize>, // in the order the rule mentions them
+    null_check: NullLikeCheck,
+}
+
+impl RowRule {
+    pub fn compile(
+        name: &str,
+        rule: &str,
+        headers: &HeaderLookup,
+        null_check: NullLikeCheck,
+    ) -> Result<Self, Box<dyn Error>> {
+        let invalid = |e: String| format!("Invalid row rule '{}': {}", rule, e);
+        let mut parser = Parser {
+            tokens: tokenize(rule).map_err(invalid)?,
+            position: 0,
+            headers,
+        };
+        let expr = parser.rule().map_err(invalid)?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid(format!("Unexpected {:?}", token)).into());
+        }
+        if !expr.is_boolean() {
+            return Err(invalid("the rule must be a comparison or condition".to_string()).into());
+        }
+
+        let mut columns = Vec::new();
+        expr.columns(&mut columns);
+        if columns.is_empty() {
+            return Err(invalid("the rule doesn't use any column".to_string()).into());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            rule: rule.to_string(),
+            expr,
+            columns,
+            null_check,
+        })
+    }
+
+    // Columns the rule reads, the first one is where violations are counted
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    fn field<'r>(&self, record: &'r csv::StringRecord, column: usize) -> Option<&'r str> {
+        record.get(column).filter(|value| {
+            !value.trim().is_empty() && !self.null_check.check_column(column, value)
+        })
+    }
+
+    fn eval<'a>(
+        &'a self,
+        expr: &'a Expr,
+        record: &'a csv::StringRecord,
+    ) -> Result<Value<'a>, Mismatch> {
+        Ok(match expr {
+            Expr::Number(number) => Value::Number(*number),
+            Expr::Text(text) => Value::Text(text),
+            Expr::Bool(value) => Value::Bool(*value),
+            Expr::Column(column) => self
+                .field(record, *column)
+                .map_or(Value::Missing, Value::Text),
+            Expr::Empty(column) => Value::Bool(self.field(record, *column).is_none()),
+            Expr::Present(column) => Value::Bool(self.field(record, *column).is_some()),
+            Expr::Neg(inner) => match number(self.eval(inner, record)?)? {
+                Some(number) => Value::Number(-number),
+                None => Value::Missing,
+            },
+            Expr::Not(inner) => from_option(boolean(self.eval(inner, record)?)?.map(|b| !b)),
+            // Unknown only matters when the other side doesn't decide the result
+            Expr::And(a, b) => {
+                let (a, b) = (
+                    boolean(self.eval(a, record)?)?,
+                    boolean(self.eval(b, record)?)?,
+                );
+                match (a, b) {
+                    (Some(false), _) | (_, Some(false)) => Value::Bool(false),
+                    (Some(true), Some(true)) => Value::Bool(true),
+                    _ => Value::Missing,
+                }
+            }
+            Expr::Or(a, b) => {
+                let (a, b) = (
+                    boolean(self.eval(a, record)?)?,
+                    boolean(self.eval(b, record)?)?,
+                );
+                match (a, b) {
+                    (Some(true), _) | (_, Some(true)) => Value::Bool(true),
+                    (Some(false), Some(false)) => Value::Bool(false),
+                    _ => Value::Missing,
+                }
+            }
+            Expr::Implies(condition, consequence) => {
+                match boolean(self.eval(condition, record)?)? {
+                    Some(true) => self.eval(consequence, record)?,
+                    _ => Value::Bool(true),
+                }
+            }
+            Expr::Compare(op, a, b) => {
+                let (a, b) = (self.eval(a, record)?, self.eval(b, record)?);
+                if a == Value::Missing || b == Value::Missing {
+                    return Ok(Value::Missing);
+                }
+                let ordering = compare_values(a, b)?.ok_or(Mismatch)?;
+                Value::Bool(match op {
+                    CompareOp::Eq => ordering == Ordering::Equal,
+                    CompareOp::Ne => ordering != Ordering::Equal,
+                    CompareOp::Lt => ordering == Ordering::Less,
+                    CompareOp::Le => ordering != Ordering::Greater,
+                    CompareOp::Gt => ordering == Ordering::Greater,
+                    CompareOp::Ge => ordering != Ordering::Less,
+                })
+            }
+            Expr::Arith(op, a, b) => {
+                let (a, b) = (
+                    number(self.eval(a, record)?)?,
+                    number(self.eval(b, record)?)?,
+                );
+                match (a, b) {
+                    (Some(a), Some(b)) => Value::Number(match op {
+                        ArithOp::Add => a + b,
+                        ArithOp::Sub => a - b,
+                        ArithOp::Mul => a * b,
+                        ArithOp::Div => a / b,
+                    }),
+                    _ => Value::Missing,
+                }
+            }
+        })
+    }
+}
+
+impl RowCheck for RowRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // False and values that can't be compared are violations, unknown is not
+    fn check_row(&self, record: &csv::StringRecord, _headers: &HeaderLookup) -> bool {
+        match self.eval(&self.expr, record) {
+            Ok(value) => value == Value::Bool(false),
+            Err(Mismatch) => true,
+        }
+    }
+
+    fn show_check_pattern(&self) -> &str {
+        &self.rule
+    }
+
+    fn column(&self) -> usize {
+        self.columns[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> HeaderLookup {
+        let names = [
+            "order_date",
+            "quantity",
+            "unit_price",
+            "email",
+            "phone",
+            "last_updated_timestamp",
+            "ship mode",
+        ];
+        HeaderLookup::new(&names.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    fn violations(rule: &str, rows: &[&[&str]]) -> Vec<bool> {
+        let headers = headers();
+        let rule = RowRule::compile(rule, rule, &headers, NullLikeCheck::new()).unwrap();
+        rows.iter()
+            .map(|row| rule.check_row(&csv::StringRecord::from(row.to_vec()), &headers))
+            .collect()
+    }
+
+    #[test]
+    fn test_row_rules() {
+        assert_eq!(
+            violations(
+                "last_updated_timestamp >= order_date",
+                &[
+                    &["1/15/2024", "", "", "", "", "2024-01-15T08:30:00Z", ""],
+                    &["1/16/2024", "", "", "", "", "2024-01-15T08:30:00Z", ""],
+                    &["", "", "", "", "", "2024-01-15T08:30:00Z", ""],
+                ]
+            ),
+            vec![false, true, false]
+        );
+        assert_eq!(
+            violations(
+                "quantity * unit_price >= 0",
+                &[
+                    &["", "2", "9.99", "", "", "", ""],
+                    &["", "-2", "9.99", "", "", "", ""],
+                    &["", "two", "9.99", "", "", "", ""],
+                    &["", "NULL", "9.99", "", "", "", ""],
+                ]
+            ),
+            vec![false, true, true, false]
+        );
+        assert_eq!(
+            violations(
+                "if empty(email) then present(phone)",
+                &[
+                    &["", "", "", "a@b.com", "", "", ""],
+                    &["", "", "", "", "555-0100", "", ""],
+                    &["", "", "", "N/A", "", "", ""],
+                ]
+            ),
+            vec![false, false, true]
+        );
+        assert_eq!(
+            violations(
+                "`ship mode` = 'air' or not (quantity > 10)",
+                &[
+                    &["", "20", "", "", "", "", "air"],
+                    &["", "20", "", "", "", "", "ground"],
+                    &["", "5", "", "", "", "", "ground"],
+                ]
+            ),
+            vec![false, true, false]
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let compile = |rule: &str| RowRule::compile(rule, rule, &headers(), NullLikeCheck::new());
+        assert!(compile("quantity >= ").is_err());
+        assert!(compile("missing_column > 0").is_err());
+        assert!(compile("quantity * 2").is_err());
+        assert!(compile("1 > 0").is_err());
+        assert!(compile("if empty(email) present(phone)").is_err());
+        assert!(compile("length(email) > 3").is_err());
+        assert!(compile("quantity > 0 )").is_err());
+
+        let rule = compile("unit_price > 0 and quantity > 0").unwrap();
+        assert_eq!(rule.columns(), &[2, 1]);
+        assert_eq!(rule.column(), 2);
+    }
+}
@@ -0,0 +1,528 @@
+use crate::nulls::resolve_column;
+use crate::{
+    CheckRegistry, CsvAggregator, DuplicateConfig, DuplicateReport, EmptyCheck, HeaderLookup,
+    NullLikeCheck, PatternCheck, RegexCheck, RowCheck, RowRule, WhiteSpaceOnlyCheck,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::sync::Arc;
+
+// Expectations for one column, a `[columns.<name or index>]` table in truesight.toml
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnRules {
+    #[serde(default)]
+    pub required: bool, // no empty, whitespace-only or NULL-like values
+    pub max_null_pct: Option<f64>,
+    pub regex: Option<String>, // a pattern or a preset like `@email`
+    #[serde(rename = "enum")]
+    pub allowed: Option<Vec<String>>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    pub unique: bool,
+}
+
+enum RuleKind {
+    Required,
+    Enum(HashSet<String>),
+    Range { min: Option<f64>, max: Option<f64> },
+    MaxLength(usize),
+}
+
+// A per-cell rule bound to one column, flags the values that break it. Runs through
+// the CheckRegistry like any other check so it is counted per chunk, in parallel
+pub struct ColumnRuleCheck {
+    name: String,
+    pattern: String,
+    column: usize,
+    kind: RuleKind,
+    null_check: NullLikeCheck,
+}
+
+impl ColumnRuleCheck {
+    fn is_missing(&self, value: &str) -> bool {
+        value.trim().is_empty() || self.null_check.check_column(self.column, value)
+    }
+
+    fn violates(&self, value: &str) -> bool {
+        if let RuleKind::Required = self.kind {
+            return self.is_missing(value);
+        }
+        // Missing values are only a problem for `required`
+        if self.is_missing(value) {
+            return false;
+        }
+
+        match &self.kind {
+            RuleKind::Required => unreachable!(),
+            RuleKind::Enum(allowed) => !allowed.contains(value),
+            RuleKind::Range { min, max } => match value.trim().parse::<f64>() {
+                Ok(number) => {
+                    min.is_some_and(|min| number < min) || max.is_some_and(|max| number > max)
+                }
+                Err(_) => true,
+            },
+            RuleKind::MaxLength(max) => value.chars().count() > *max,
+        }
+    }
+}
+
+impl PatternCheck for ColumnRuleCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check(&self, value: &str) -> bool {
+        self.violates(value)
+    }
+
+    fn check_column(&self, column: usize, value: &str) -> bool {
+        column == self.column && self.violates(value)
+    }
+
+    fn show_check_pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    fn column(&self) -> Option<usize> {
+        Some(self.column)
+    }
+}
+
+// How a rule is judged once the whole input has been read
+#[derive(Debug, Clone)]
+enum Outcome {
+    Cells(String), // name of the registered ColumnRuleCheck, RegexCheck or RowRule
+    NullPct(f64),
+    Unique,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    column: usize,
+    column_name: String,
+    rule: String,
+    expectation: String,
+    outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleResult {
+    pub column_index: usize,
+    pub column: String,
+    pub rule: String,
+    pub expectation: String,
+    pub violations: u64,
+    pub passed: bool,
+}
+
+// The rules file compiled against a header row
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+    checks: Vec<Arc<dyn PatternCheck>>,
+    row_checks: Vec<Arc<dyn RowCheck>>,
+}
+
+impl RuleSet {
+    pub fn compile(
+        columns: &BTreeMap<String, ColumnRules>,
+        headers: &[String],
+        null_check: &NullLikeCheck,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut set = Self::default();
+
+        // Rules come out in column order whatever the order of the file
+        let mut resolved = columns
+            .iter()
+            .map(|(column, rules)| Ok((resolve_column(column, headers)?, rules)))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        resolved.sort_by_key(|(column, _)| *column);
+
+        for (column, rules) in resolved {
+            let column_name = &headers[column];
+            let add_check =
+                |set: &mut Self, rule: &'static str, expectation: String, kind: RuleKind| {
+                    let name = format!("{}: {}", column_name, rule);
+                    set.checks.push(Arc::new(ColumnRuleCheck {
+                        name: name.clone(),
+                        pattern: expectation.clone(),
+                        column,
+                        kind,
+                        null_check: null_check.clone(),
+                    }));
+                    set.rules.push(CompiledRule {
+                        column,
+                        column_name: column_name.clone(),
+                        rule: rule.to_string(),
+                        expectation,
+                        outcome: Outcome::Cells(name),
+                    });
+                };
+
+            if rules.required {
+                add_check(
+                    &mut set,
+                    "required",
+                    "no missing values".to_string(),
+                    RuleKind::Required,
+                );
+            }
+            if let Some(pattern) = &rules.regex {
+                let name = format!("{}: regex", column_name);
+                let check = RegexCheck::new(&name, column, pattern, null_check.clone())?;
+                set.rules.push(CompiledRule {
+                    column,
+                    column_name: column_name.clone(),
+                    rule: "regex".to_string(),
+                    expectation: format!("matches {}", check.show_check_pattern()),
+                    outcome: Outcome::Cells(name),
+                });
+                set.checks.push(Arc::new(check));
+            }
+            if let Some(allowed) = &rules.allowed {
+                add_check(
+                    &mut set,
+                    "enum",
+                    format!("one of {}", allowed.join(", ")),
+                    RuleKind::Enum(allowed.iter().cloned().collect()),
+                );
+            }
+            if rules.min.is_some() || rules.max.is_some() {
+                let bound = |b: Option<f64>| b.map_or("..".to_string(), |b| b.to_string());
+                add_check(
+                    &mut set,
+                    "range",
+                    format!("number in [{}, {}]", bound(rules.min), bound(rules.max)),
+                    RuleKind::Range {
+                        min: rules.min,
+                        max: rules.max,
+                    },
+                );
+            }
+            if let Some(max_length) = rules.max_length {
+                add_check(
+                    &mut set,
+                    "max_length",
+                    format!("at most {} characters", max_length),
+                    RuleKind::MaxLength(max_length),
+                );
+            }
+
+            if let Some(max_null_pct) = rules.max_null_pct {
+                if !(0.0..=100.0).contains(&max_null_pct) {
+                    return Err(format!(
+                        "max_null_pct must be between 0 and 100 for column '{}'",
+                        column_name
+                    )
+                    .into());
+                }
+                set.rules.push(CompiledRule {
+                    column,
+                    column_name: column_name.clone(),
+                    rule: "max_null_pct".to_string(),
+                    expectation: format!("at most {}% missing", max_null_pct),
+                    outcome: Outcome::NullPct(max_null_pct),
+                });
+            }
+            if rules.unique {
+                set.rules.push(CompiledRule {
+                    column,
+                    column_name: column_name.clone(),
+                    rule: "unique".to_string(),
+                    expectation: "no repeated values".to_string(),
+                    outcome: Outcome::Unique,
+                });
+            }
+        }
+
+        Ok(set)
+    }
+
+    // Rules over several fields of a row, keyed by name. Rules given on the command
+    // line are named after themselves
+    pub fn add_row_rules(
+        &mut self,
+        rows: &BTreeMap<String, String>,
+        headers: &[String],
+        null_check: &NullLikeCheck,
+    ) -> Result<(), Box<dyn Error>> {
+        let lookup = HeaderLookup::new(headers);
+        for (name, text) in rows {
+            let row_rule = RowRule::compile(name, text, &lookup, null_check.clone())?;
+            let column_names: Vec<&str> = row_rule
+                .columns()
+                .iter()
+                .map(|column| headers[*column].as_str())
+                .collect();
+            self.rules.push(CompiledRule {
+                column: row_rule.column(),
+                column_name: column_names.join(", "),
+                rule: if name == text {
+                    "row".to_string()
+                } else {
+                    name.clone()
+                },
+                expectation: text.clone(),
+                outcome: Outcome::Cells(name.clone()),
+            });
+            self.row_checks.push(Arc::new(row_rule));
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Add the per-cell and row rules to the checks run on every chunk
+    pub fn register(&self, registry: &mut CheckRegistry) -> Result<(), Box<dyn Error>> {
+        for check in &self.checks {
+            registry.register_shared(check.clone())?;
+        }
+        for check in &self.row_checks {
+            registry.register_row_check(check.clone())?;
+        }
+        Ok(())
+    }
+
+    // Uniqueness is checked by a duplicate key detector on the column
+    pub fn duplicate_configs(&self, base: &DuplicateConfig) -> Vec<DuplicateConfig> {
+        self.rules
+            .iter()
+            .filter(|rule| matches!(rule.outcome, Outcome::Unique))
+            .map(|rule| DuplicateConfig {
+                key_columns: Some(vec![rule.column]),
+                ..base.clone()
+            })
+            .collect()
+    }
+
+    // Pass/fail per rule. `duplicate_reports` are the reports of the detectors built from
+    // duplicate_configs, not the ones of --duplicates/--key. They are per input, so
+    // `unique` doesn't see values repeated across files
+    pub fn evaluate(
+        &self,
+        aggregator: &CsvAggregator,
+        duplicate_reports: &[DuplicateReport],
+    ) -> Vec<RuleResult> {
+        let total_rows = aggregator.total_rows();
+
+        self.rules
+            .iter()
+            .map(|rule| {
+                let stats = aggregator.column_stats().get(rule.column);
+                let (violations, passed) = match &rule.outcome {
+                    Outcome::Cells(name) => {
+                        let count = stats.map_or(0, |s| s.count_for(name)) as u64;
+                        (count, count == 0)
+                    }
+                    Outcome::NullPct(max_percent) => {
+                        let count = stats.map_or(0, |s| {
+                            s.count_for(NullLikeCheck::NAME)
+                                + s.count_for(EmptyCheck::NAME)
+                                + s.count_for(WhiteSpaceOnlyCheck::NAME)
+                        });
+                        let percent = if total_rows > 0 {
+                            count as f64 / total_rows as f64 * 100.0
+                        } else {
+                            0.0
+                        };
+                        (count as u64, percent <= *max_percent)
+                    }
+                    Outcome::Unique => {
+                        let key = Some(vec![rule.column_name.clone()]);
+                        let count = duplicate_reports
+                            .iter()
+                            .filter(|report| report.key_columns == key)
+                            .map(|report| report.duplicate_rows)
+                            .sum();
+                        (count, count == 0)
+                    }
+                };
+
+                RuleResult {
+                    column_index: rule.column,
+                    column: rule.column_name.clone(),
+                    rule: rule.rule.clone(),
+                    expectation: rule.expectation.clone(),
+                    violations,
+                    passed,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_csv_chunks, CsvChunkIterator, ProcessingConfig};
+
+    fn headers() -> Vec<String> {
+        ["id", "email", "status", "qty"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn rules(toml: &str) -> BTreeMap<String, ColumnRules> {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_rule_checks() {
+        let columns = rules(
+            r#"
+            [email]
+            required = true
+            regex = '^[^@\s]+@[^@\s]+$'
+            max_length = 12
+
+            [3]
+            min = 0
+            max = 10
+
+            [status]
+            enum = ["open", "closed"]
+            "#,
+        );
+        let set = RuleSet::compile(&columns, &headers(), &NullLikeCheck::new()).unwrap();
+        let mut registry = CheckRegistry::empty();
+        set.register(&mut registry).unwrap();
+
+        let check = |name: &str| registry.get(name).unwrap().clone();
+        assert!(check("email: required").check_column(1, "NULL"));
+        assert!(!check("email: required").check_column(0, ""));
+        // Missing values only break `required`
+        assert!(!check("email: regex").check_column(1, ""));
+        assert!(check("email: regex").check_column(1, "not-an-email"));
+        assert!(check("email: max_length").check_column(1, "someone@example.com"));
+        assert!(check("status: enum").check_column(2, "pending"));
+        assert!(!check("status: enum").check_column(2, "open"));
+        assert!(check("qty: range").check_column(3, "11"));
+        assert!(check("qty: range").check_column(3, "ten"));
+        assert!(!check("qty: range").check_column(3, "2.5"));
+
+        assert!(RuleSet::compile(
+            &rules("[nope]\nrequired = true"),
+            &headers(),
+            &NullLikeCheck::new()
+        )
+        .is_err());
+        assert!(RuleSet::compile(
+            &rules("[id]\nregex = '('"),
+            &headers(),
+            &NullLikeCheck::new()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let columns = rules(
+            r#"
+            [id]
+            unique = true
+            [email]
+            required = true
+            max_null_pct = 40
+            "#,
+        );
+        let headers = headers();
+        let set = RuleSet::compile(&columns, &headers, &NullLikeCheck::new()).unwrap();
+        let mut registry = CheckRegistry::new();
+        set.register(&mut registry).unwrap();
+        assert_eq!(set.duplicate_configs(&DuplicateConfig::default()).len(), 1);
+
+        let data = "id,email,status,qty\n1,a@b.c,open,1\n2,,open,1\n3,c@d.e,open,1\n";
+        let mut rdr = csv::Reader::from_reader(data.as_bytes());
+        let config = ProcessingConfig {
+            chunk_size: 2,
+            registry: registry.clone(),
+            ..Default::default()
+        };
+        let mut aggregator = CsvAggregator::new(headers, 2, &registry);
+        process_csv_chunks(
+            CsvChunkIterator::new(rdr.records(), 2),
+            config,
+            &mut aggregator,
+        )
+        .unwrap();
+
+        let duplicates = DuplicateReport {
+            key_columns: Some(vec!["id".to_string()]),
+            duplicate_rows: 0,
+            duplicate_groups: 0,
+            groups: Vec::new(),
+        };
+        let outcome: Vec<(String, u64, bool)> = set
+            .evaluate(&aggregator, &[duplicates])
+            .into_iter()
+            .map(|r| (r.rule, r.violations, r.passed))
+            .collect();
+        assert_eq!(
+            outcome,
+            vec![
+                ("unique".to_string(), 0, true),
+                ("required".to_string(), 1, false),
+                ("max_null_pct".to_string(), 1, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_row_rules() {
+        let headers = headers();
+        let rows: BTreeMap<String, String> = [
+            ("qty_positive", "qty > 0"),
+            ("open_needs_email", "if status = 'open' then present(email)"),
+        ]
+        .into_iter()
+        .map(|(name, rule)| (name.to_string(), rule.to_string()))
+        .collect();
+        let mut set = RuleSet::default();
+        set.add_row_rules(&rows, &headers, &NullLikeCheck::new())
+            .unwrap();
+        let mut registry = CheckRegistry::new();
+        set.register(&mut registry).unwrap();
+        registry.set_headers(&headers);
+        assert!(set.register(&mut registry).is_err()); // names are taken
+
+        let data = "id,email,status,qty\n1,,open,1\n2,,closed,0\n3,c@d.e,open,-1\n";
+        let mut rdr = csv::Reader::from_reader(data.as_bytes());
+        let config = ProcessingConfig {
+            chunk_size: 2,
+            registry: registry.clone(),
+            ..Default::default()
+        };
+        let mut aggregator = CsvAggregator::new(headers, 2, &registry);
+        process_csv_chunks(
+            CsvChunkIterator::new(rdr.records(), 2),
+            config,
+            &mut aggregator,
+        )
+        .unwrap();
+
+        let outcome: Vec<(usize, String, String, u64)> = set
+            .evaluate(&aggregator, &[])
+            .into_iter()
+            .map(|r| (r.column_index, r.column, r.rule, r.violations))
+            .collect();
+        assert_eq!(
+            outcome,
+            vec![
+                (
+                    2,
+                    "status, email".to_string(),
+                    "open_needs_email".to_string(),
+                    1
+                ),
+                (3, "qty".to_string(), "qty_positive".to_string(), 2),
+            ]
+        );
+    }
+}
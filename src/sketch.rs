@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+// 2^12 registers gives roughly 1.6% standard error in 4 KB per column
+const HLL_PRECISION: u32 = 12;
+const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+
+// Number of counters kept per column for heavy hitters
+const DEFAULT_HEAVY_HITTER_CAPACITY: usize = 32;
+
+// DefaultHasher::new() always uses the same keys, so hashes agree across chunks and threads
+pub fn hash_value(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// HyperLogLog distinct count estimate. Merging takes the max of each register,
+// which gives exactly the sketch a single pass over both inputs would have built.
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        // Rank of the first set bit in the remaining bits, the sentinel bit caps it
+        let remaining = (hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1));
+        let rank = remaining.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other_register) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other_register);
+        }
+    }
+
+    pub fn estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-(register as i32)))
+            .sum();
+        let raw = alpha * m * m / sum;
+
+        // Linear counting is much more accurate for small cardinalities
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            raw.round() as u64
+        }
+    }
+}
+
+// Misra-Gries heavy hitters summary with a fixed number of counters.
+// Counts are lower bounds, off by at most total / (capacity + 1).
+#[derive(Debug, Clone)]
+pub struct HeavyHitters {
+    capacity: usize,
+    counters: HashMap<String, u64>,
+    total: u64,
+}
+
+impl Default for HeavyHitters {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEAVY_HITTER_CAPACITY)
+    }
+}
+
+impl HeavyHitters {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    pub fn insert(&mut self, value: &str) {
+        self.total += 1;
+        if let Some(count) = self.counters.get_mut(value) {
+            *count += 1;
+        } else if self.counters.len() < self.capacity {
+            self.counters.insert(value.to_string(), 1);
+        } else {
+            // Decrement everything, the new value is dropped along with any counter hitting zero
+            self.counters.retain(|_, count| {
+                *count -= 1;
+                *count > 0
+            });
+        }
+    }
+
+    pub fn merge(&mut self, other: &HeavyHitters) {
+        self.total += other.total;
+        for (value, count) in &other.counters {
+            *self.counters.entry(value.clone()).or_insert(0) += count;
+        }
+
+        if self.counters.len() > self.capacity {
+            // Subtract the (capacity + 1)th largest count and keep what stays positive
+            let mut counts: Vec<u64> = self.counters.values().copied().collect();
+            counts.sort_unstable_by(|a, b| b.cmp(a));
+            let cutoff = counts[self.capacity];
+            self.counters.retain(|_, count| {
+                *count = count.saturating_sub(cutoff);
+                *count > 0
+            });
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    // Most frequent values first, ties broken by value so output is stable
+    pub fn top(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut top: Vec<(&str, u64)> = self
+            .counters
+            .iter()
+            .map(|(value, count)| (value.as_str(), *count))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hll_estimates() {
+        let mut small = HyperLogLog::default();
+        for value in ["a", "b", "c", "a", "b"] {
+            small.insert_hash(hash_value(value));
+        }
+        assert_eq!(small.estimate(), 3);
+
+        let mut large = HyperLogLog::default();
+        for i in 0..100_000 {
+            large.insert_hash(hash_value(&i.to_string()));
+        }
+        let estimate = large.estimate() as f64;
+        assert!(
+            (estimate - 100_000.0).abs() / 100_000.0 < 0.05,
+            "{}",
+            estimate
+        );
+    }
+
+    #[test]
+    fn test_hll_merge() {
+        let mut first = HyperLogLog::default();
+        let mut second = HyperLogLog::default();
+        let mut whole = HyperLogLog::default();
+        for i in 0..20_000 {
+            let hash = hash_value(&format!("customer-{}", i));
+            whole.insert_hash(hash);
+            if i % 2 == 0 {
+                first.insert_hash(hash);
+            } else {
+                second.insert_hash(hash);
+            }
+        }
+        first.merge(&second);
+        assert_eq!(first.estimate(), whole.estimate());
+    }
+
+    #[test]
+    fn test_heavy_hitters() {
+        // SKU456 is 20% of the stream, above the 1 / (capacity + 1) guarantee
+        let mut hitters = HeavyHitters::new(5);
+        for i in 0..1_000 {
+            hitters.insert("SKU123");
+            if i % 2 == 0 {
+                hitters.insert("SKU456");
+            }
+            hitters.insert(&format!("rare-{}", i));
+        }
+
+        let top = hitters.top(2);
+        assert_eq!(top[0].0, "SKU123");
+        assert_eq!(top[1].0, "SKU456");
+        // Lower bounds within total / (capacity + 1)
+        let bound = hitters.total() / 6;
+        assert!(top[0].1 <= 1_000 && top[0].1 + bound >= 1_000);
+    }
+
+    #[test]
+    fn test_heavy_hitters_merge() {
+        let mut first = HeavyHitters::new(2);
+        let mut second = HeavyHitters::new(2);
+        for _ in 0..10 {
+            first.insert("x");
+            second.insert("x");
+            second.insert("y");
+        }
+        first.insert("z");
+        second.insert("w");
+
+        first.merge(&second);
+        assert_eq!(first.total(), 32);
+        assert_eq!(first.top(1), vec![("x", 18)]);
+        assert!(first.top(10).len() <= 2);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::inference::{classify_value, InferredType};
+use crate::input::{open_input, InputReader};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+// How much of the file is looked at to guess the dialect
+pub const SNIFF_SAMPLE_BYTES: usize = 64 * 1024;
+
+// Rows used for the delimiter and header votes
+const SNIFF_ROWS: usize = 50;
+
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+const QUOTE_CANDIDATES: [u8; 2] = [b'"', b'\''];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineTerminator {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineTerminator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineTerminator::Lf => "LF",
+            LineTerminator::CrLf => "CRLF",
+            LineTerminator::Cr => "CR",
+        }
+    }
+
+    // The csv default already accepts LF and CRLF, only a bare CR needs to be spelled out
+    pub fn to_csv_terminator(self) -> csv::Terminator {
+        match self {
+            LineTerminator::Cr => csv::Terminator::Any(b'\r'),
+            LineTerminator::Lf | LineTerminator::CrLf => csv::Terminator::CRLF,
+        }
+    }
+}
+
+// Delimiter, quote, header and line terminator of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Dialect {
+    #[serde(serialize_with = "serialize_byte")]
+    pub delimiter: u8,
+    #[serde(serialize_with = "serialize_byte")]
+    pub quote: u8,
+    pub has_headers: bool,
+    pub line_terminator: LineTerminator,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            has_headers: true,
+            line_terminator: LineTerminator::Lf,
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "delimiter {}, quote {}, header row: {}, line terminator: {}",
+            display_byte(self.delimiter),
+            display_byte(self.quote),
+            if self.has_headers { "yes" } else { "no" },
+            self.line_terminator.as_str()
+        )
+    }
+}
+
+fn display_byte(byte: u8) -> String {
+    match byte {
+        b'\t' => "'\\t'".to_string(),
+        _ => format!("'{}'", byte as char),
+    }
+}
+
+fn serialize_byte<S: Serializer>(byte: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&(*byte as char).to_string())
+}
+
+// Read the start of a (possibly compressed) file and guess its dialect
+pub fn sniff_file(path: &Path) -> io::Result<Dialect> {
+    let (_compression, input) = open_input(path)?;
+    let (dialect, _input) = sniff_reader(input)?;
+    Ok(dialect)
+}
+
+// Guess the dialect from the start of a stream, the sample is put back in front
+// of the returned reader so nothing is lost (works for stdin too)
+pub fn sniff_reader(mut input: InputReader) -> io::Result<(Dialect, InputReader)> {
+    let mut sample = Vec::with_capacity(SNIFF_SAMPLE_BYTES);
+    input
+        .by_ref()
+        .take(SNIFF_SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+    let dialect = sniff(&sample, sample.len() < SNIFF_SAMPLE_BYTES);
+    Ok((dialect, Box::new(Cursor::new(sample).chain(input))))
+}
+
+// Guess the dialect from a sample, `complete` is false when the sample was cut off
+pub fn sniff(sample: &[u8], complete: bool) -> Dialect {
+    let line_terminator = detect_line_terminator(sample);
+
+    // Drop the last, probably partial, line of a truncated sample
+    let sample = if complete {
+        sample
+    } else {
+        match sample.iter().rposition(|&b| b == b'\n' || b == b'\r') {
+            Some(end) => &sample[..=end],
+            None => sample,
+        }
+    };
+
+    let terminator = line_terminator.to_csv_terminator();
+    let quote = detect_quote(sample);
+    let delimiter = detect_delimiter(sample, quote, terminator);
+    let rows = sample_rows(sample, delimiter, quote, terminator);
+
+    Dialect {
+        delimiter,
+        quote,
+        has_headers: detect_header(&rows),
+        line_terminator,
+    }
+}
+
+fn detect_line_terminator(sample: &[u8]) -> LineTerminator {
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut i = 0;
+    while i < sample.len() {
+        match sample[i] {
+            b'\r' if sample.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if crlf >= lf && crlf >= cr && crlf > 0 {
+        LineTerminator::CrLf
+    } else if cr > lf {
+        LineTerminator::Cr
+    } else {
+        LineTerminator::Lf
+    }
+}
+
+fn sample_rows(
+    sample: &[u8],
+    delimiter: u8,
+    quote: u8,
+    terminator: csv::Terminator,
+) -> Vec<csv::StringRecord> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .quote(quote)
+        .terminator(terminator)
+        .from_reader(sample)
+        .byte_records()
+        .take(SNIFF_ROWS)
+        .map_while(Result::ok)
+        .map(csv::StringRecord::from_byte_record_lossy) // text in another encoding still counts
+        .collect()
+}
+
+// Pick the candidate that splits the most rows into the same number (> 1) of fields
+fn detect_delimiter(sample: &[u8], quote: u8, terminator: csv::Terminator) -> u8 {
+    let mut best = (b',', 0, 0);
+    for &delimiter in &DELIMITER_CANDIDATES {
+        let rows = sample_rows(sample, delimiter, quote, terminator);
+
+        let mut field_counts: Vec<usize> = rows.iter().map(|row| row.len()).collect();
+        field_counts.sort_unstable();
+        let (fields, consistent_rows) = field_counts
+            .chunk_by(|a, b| a == b)
+            .map(|run| (run[0], run.len()))
+            .max_by_key(|&(fields, rows)| (rows, fields))
+            .unwrap_or((1, 0));
+
+        if fields > 1 && (consistent_rows, fields) > (best.1, best.2) {
+            best = (delimiter, consistent_rows, fields);
+        }
+    }
+    best.0
+}
+
+// Count quote characters that open or close a field, double quotes win ties.
+// Runs before the delimiter is known so any candidate counts as a field boundary
+fn detect_quote(sample: &[u8]) -> u8 {
+    let is_boundary = |byte: Option<&u8>| match byte {
+        None => true,
+        Some(b) => *b == b'\n' || *b == b'\r' || DELIMITER_CANDIDATES.contains(b),
+    };
+
+    let quoted_fields = |quote: u8| {
+        let (mut opening, mut closing) = (0, 0);
+        for (i, &byte) in sample.iter().enumerate() {
+            if byte != quote {
+                continue;
+            }
+            if i == 0 || is_boundary(sample.get(i - 1)) {
+                opening += 1;
+            } else if is_boundary(sample.get(i + 1)) {
+                closing += 1;
+            }
+        }
+        opening.min(closing)
+    };
+
+    QUOTE_CANDIDATES
+        .iter()
+        .copied()
+        .max_by_key(|&quote| (quoted_fields(quote), quote == b'"'))
+        .unwrap_or(b'"')
+}
+
+// Header unless the first row clearly is data: a column whose values share a type
+// (number, date, ...) has a first value of that type too, and no such column has a
+// first value that doesn't fit. Columns of text say nothing either way, renaming real
+// headers to col_N is worse than reading one row of data as names.
+fn detect_header(rows: &[csv::StringRecord]) -> bool {
+    let Some((first, data)) = rows.split_first() else {
+        return true;
+    };
+
+    let mut typed_matches = 0;
+    for (col, header) in first.iter().enumerate() {
+        let values: Vec<&str> = data
+            .iter()
+            .filter_map(|row| row.get(col))
+            .filter(|value| !value.trim().is_empty())
+            .collect();
+        let Some(&sample_value) = values.first() else {
+            continue;
+        };
+
+        let value_type = classify_value(sample_value);
+        if value_type == InferredType::String
+            || values
+                .iter()
+                .any(|value| classify_value(value) != value_type)
+        {
+            continue;
+        }
+        if classify_value(header) != value_type {
+            return true;
+        }
+        typed_matches += 1;
+    }
+
+    typed_matches == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiters() {
+        let semicolon = b"id;name;price\n1;apple;1,50\n2;pear;2,10\n";
+        let dialect = sniff(semicolon, true);
+        assert_eq!(dialect.delimiter, b';');
+        assert!(dialect.has_headers);
+
+        let tab = b"id\tname\n1\tapple\n2\tpear\n";
+        assert_eq!(sniff(tab, true).delimiter, b'\t');
+
+        // Delimiters inside quotes don't count
+        let pipe = b"id|note\r\n1|\"a,b,c\"\r\n2|\"d,e\"\r\n";
+        let dialect = sniff(pipe, true);
+        assert_eq!(dialect.delimiter, b'|');
+        assert_eq!(dialect.line_terminator, LineTerminator::CrLf);
+
+        // A single column falls back to comma
+        assert_eq!(sniff(b"id\n1\n2\n", true).delimiter, b',');
+    }
+
+    #[test]
+    fn test_sniff_quote_and_terminator() {
+        let single = b"id,name\r1,'Smith, J'\r2,'Doe, A'\r";
+        let dialect = sniff(single, true);
+        assert_eq!(dialect.quote, b'\'');
+        assert_eq!(dialect.delimiter, b',');
+        assert_eq!(dialect.line_terminator, LineTerminator::Cr);
+
+        assert_eq!(sniff(b"a,b\n\"1\",\"2\"\n", true).quote, b'"');
+    }
+
+    #[test]
+    fn test_sniff_header() {
+        assert!(sniff(b"id,amount\n1,2.50\n2,3.75\n", true).has_headers);
+        assert!(!sniff(b"1,2.50\n2,3.75\n3,4.00\n", true).has_headers);
+        assert!(!sniff(b"US,2024-01-01\nDE,2024-01-02\n", true).has_headers);
+
+        // Text columns are no evidence, even when the names are as wide as the values
+        assert!(sniff(b"id,sku\nab,xyz\ncd,uvw\n", true).has_headers);
+        assert!(sniff(b"name,city\nAnn,Oslo\nBob,Rome\n", true).has_headers);
+    }
+
+    #[test]
+    fn test_truncated_sample_ignores_partial_line() {
+        // The cut off last line would otherwise look like a 2 field row
+        let sample = b"a;b;c\n1;2;3\n4;5;6\n7;8";
+        let dialect = sniff(sample, false);
+        assert_eq!(dialect.delimiter, b';');
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+// Only the first ragged rows keep their line numbers, the counts stay exact
+pub const RAGGED_ROWS_KEPT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RaggedRow {
+    pub line: u64,
+    pub expected_fields: usize,
+    pub actual_fields: usize,
+}
+
+// Rows whose field count doesn't match the header, only seen in tolerant mode
+// since the strict reader aborts on the first one
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RowShapeStats {
+    pub short_rows: usize,
+    pub long_rows: usize,
+    pub rows: Vec<RaggedRow>, // in file order, capped at RAGGED_ROWS_KEPT
+}
+
+impl RowShapeStats {
+    pub fn from_records(records: &[csv::StringRecord], expected_fields: usize) -> Self {
+        let mut stats = Self::default();
+        for record in records {
+            let actual_fields = record.len();
+            if actual_fields == expected_fields {
+                continue;
+            }
+
+            if actual_fields < expected_fields {
+                stats.short_rows += 1;
+            } else {
+                stats.long_rows += 1;
+            }
+            if stats.rows.len() < RAGGED_ROWS_KEPT {
+                stats.rows.push(RaggedRow {
+                    line: record.position().map(|pos| pos.line()).unwrap_or(0),
+                    expected_fields,
+                    actual_fields,
+                });
+            }
+        }
+        stats
+    }
+
+    // Chunks are merged in file order so the kept rows stay sorted
+    pub fn merge(&mut self, other: &RowShapeStats) {
+        self.short_rows += other.short_rows;
+        self.long_rows += other.long_rows;
+        let room = RAGGED_ROWS_KEPT.saturating_sub(self.rows.len());
+        self.rows.extend(other.rows.iter().take(room));
+    }
+
+    pub fn ragged_rows(&self) -> usize {
+        self.short_rows + self.long_rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ragged_rows() {
+        let data = "a,b,c\n1,2,3\n1,2\n1,2,3,4\n1,2,3\n";
+        let mut rdr = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(data.as_bytes());
+        let records: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+
+        let first = RowShapeStats::from_records(&records[..2], 3);
+        let second = RowShapeStats::from_records(&records[2..], 3);
+        assert_eq!(first.short_rows, 1);
+        assert_eq!(second.long_rows, 1);
+
+        let mut merged = RowShapeStats::default();
+        merged.merge(&first);
+        merged.merge(&second);
+        assert_eq!(merged.ragged_rows(), 2);
+        assert_eq!(
+            merged.rows,
+            vec![
+                RaggedRow {
+                    line: 3,
+                    expected_fields: 3,
+                    actual_fields: 2
+                },
+                RaggedRow {
+                    line: 4,
+                    expected_fields: 3,
+                    actual_fields: 4
+                },
+            ]
+        );
+    }
+}
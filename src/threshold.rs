@@ -0,0 +1,263 @@
+use crate::{CheckRegistry, CsvAggregator, EmptyCheck, NullLikeCheck, WhiteSpaceOnlyCheck};
+use serde::Serialize;
+use std::error::Error;
+use std::str::FromStr;
+
+// Which columns a threshold applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnSelector {
+    All,
+    Name(String),
+}
+
+impl ColumnSelector {
+    fn matches(&self, header: &str) -> bool {
+        match self {
+            ColumnSelector::All => true,
+            ColumnSelector::Name(name) => name == header,
+        }
+    }
+}
+
+// A `COLUMN=PCT` limit as given on the command line, e.g. `email=5` or `*=10`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnLimit {
+    pub column: ColumnSelector,
+    pub max_percent: f64,
+}
+
+impl FromStr for ColumnLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on the last '=' so column names containing '=' still work
+        let (column, pct) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Expected COLUMN=PCT but got '{}'", s))?;
+
+        let max_percent: f64 = pct
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid percentage '{}' in '{}'", pct, s))?;
+        if !(0.0..=100.0).contains(&max_percent) {
+            return Err(format!("Percentage must be between 0 and 100 in '{}'", s));
+        }
+
+        let column = match column.trim() {
+            "*" => ColumnSelector::All,
+            "" => return Err(format!("Missing column name in '{}'", s)),
+            name => ColumnSelector::Name(name.to_string()),
+        };
+
+        Ok(ColumnLimit {
+            column,
+            max_percent,
+        })
+    }
+}
+
+// Maps the short CLI names onto the registered check names, anything else is used as is
+pub fn resolve_check_name(alias: &str) -> &str {
+    match alias.to_ascii_lowercase().as_str() {
+        "null" | "null-like" => NullLikeCheck::NAME,
+        "empty" => EmptyCheck::NAME,
+        "whitespace" => WhiteSpaceOnlyCheck::NAME,
+        _ => alias,
+    }
+}
+
+// Maximum percentage of a column's rows that may be flagged by a check
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdRule {
+    pub check_name: String,
+    pub limit: ColumnLimit,
+}
+
+impl ThresholdRule {
+    pub fn new(check_name: &str, limit: ColumnLimit) -> Self {
+        Self {
+            check_name: check_name.to_string(),
+            limit,
+        }
+    }
+
+    // Fail on any occurrence of the check in any column
+    pub fn fail_on(check_name: &str) -> Self {
+        Self::new(
+            check_name,
+            ColumnLimit {
+                column: ColumnSelector::All,
+                max_percent: 0.0,
+            },
+        )
+    }
+
+    // Make sure the rule refers to a real check and column before processing starts
+    pub fn validate(
+        &self,
+        headers: &[String],
+        registry: &CheckRegistry,
+    ) -> Result<(), Box<dyn Error>> {
+        if registry.get(&self.check_name).is_none() {
+            return Err(format!("Threshold refers to unknown check '{}'", self.check_name).into());
+        }
+        if let ColumnSelector::Name(name) = &self.limit.column {
+            if !headers.iter().any(|header| header == name) {
+                return Err(format!("Threshold refers to unknown column '{}'", name).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdViolation {
+    pub column_index: usize,
+    pub column_name: String,
+    pub check_name: String,
+    pub count: usize,
+    pub percent: f64,
+    pub max_percent: f64,
+}
+
+// Check every rule against the aggregated per-column stats
+pub fn evaluate_thresholds(
+    aggregator: &CsvAggregator,
+    rules: &[ThresholdRule],
+) -> Vec<ThresholdViolation> {
+    let total_rows = aggregator.total_rows();
+    let mut violations = Vec::new();
+
+    for rule in rules {
+        for (col_idx, (header, stats)) in aggregator
+            .headers()
+            .iter()
+            .zip(aggregator.column_stats())
+            .enumerate()
+        {
+            if !rule.limit.column.matches(header) {
+                continue;
+            }
+
+            let count = stats.count_for(&rule.check_name);
+            let percent = if total_rows > 0 {
+                (count as f64 / total_rows as f64) * 100.0
+            } else {
+                0.0
+            };
+
+            if count > 0 && percent > rule.limit.max_percent {
+                violations.push(ThresholdViolation {
+                    column_index: col_idx,
+                    column_name: header.clone(),
+                    check_name: rule.check_name.clone(),
+                    count,
+                    percent,
+                    max_percent: rule.limit.max_percent,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkProcessingResult, InvalidUtf8Stats, RowShapeStats};
+
+    fn aggregator() -> CsvAggregator {
+        let registry = CheckRegistry::new();
+        let headers = vec!["id".to_string(), "email".to_string()];
+        let mut aggregator = CsvAggregator::new(headers, 10, &registry);
+        aggregator.add_chunk_results(&ChunkProcessingResult {
+            chunk_number: 1,
+            rows_processed: 100,
+            check_counts: [
+                (
+                    NullLikeCheck::NAME.to_string(),
+                    [(1, 6)].into_iter().collect(),
+                ),
+                (
+                    EmptyCheck::NAME.to_string(),
+                    [(0, 5), (1, 20)].into_iter().collect(),
+                ),
+                (
+                    WhiteSpaceOnlyCheck::NAME.to_string(),
+                    [(0, 1)].into_iter().collect(),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            column_profiles: Vec::new(),
+            row_shape: RowShapeStats::default(),
+            invalid_utf8: InvalidUtf8Stats::default(),
+            findings: Vec::new(),
+        });
+        aggregator
+    }
+
+    #[test]
+    fn test_parse_column_limit() {
+        let limit: ColumnLimit = "email=5".parse().unwrap();
+        assert_eq!(limit.column, ColumnSelector::Name("email".to_string()));
+        assert_eq!(limit.max_percent, 5.0);
+
+        let limit: ColumnLimit = "*=10.5".parse().unwrap();
+        assert_eq!(limit.column, ColumnSelector::All);
+        assert_eq!(limit.max_percent, 10.5);
+
+        assert!("email".parse::<ColumnLimit>().is_err());
+        assert!("email=abc".parse::<ColumnLimit>().is_err());
+        assert!("email=150".parse::<ColumnLimit>().is_err());
+        assert!("=5".parse::<ColumnLimit>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_thresholds() {
+        let aggregator = aggregator();
+        let rules = vec![
+            ThresholdRule::new(NullLikeCheck::NAME, "email=5".parse().unwrap()),
+            ThresholdRule::new(EmptyCheck::NAME, "*=10".parse().unwrap()),
+        ];
+
+        let violations = evaluate_thresholds(&aggregator, &rules);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].column_name, "email");
+        assert_eq!(violations[0].check_name, NullLikeCheck::NAME);
+        assert_eq!(violations[0].count, 6);
+        // id is at 5% empty so only email breaks the 10% limit
+        assert_eq!(violations[1].column_name, "email");
+        assert_eq!(violations[1].percent, 20.0);
+    }
+
+    #[test]
+    fn test_fail_on() {
+        let aggregator = aggregator();
+        let violations = evaluate_thresholds(
+            &aggregator,
+            &[ThresholdRule::fail_on(resolve_check_name("whitespace"))],
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].column_name, "id");
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        let registry = CheckRegistry::new();
+        let headers = vec!["id".to_string()];
+
+        assert!(ThresholdRule::fail_on(EmptyCheck::NAME)
+            .validate(&headers, &registry)
+            .is_ok());
+        assert!(ThresholdRule::fail_on("NotACheck")
+            .validate(&headers, &registry)
+            .is_err());
+        assert!(
+            ThresholdRule::new(EmptyCheck::NAME, "missing=5".parse().unwrap())
+                .validate(&headers, &registry)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::next_chunk;
+use csv::{ByteRecord, StringRecord};
+use serde::Serialize;
+use std::io::Read;
+
+// Only the first invalid cells keep their line numbers, the counts stay exact
+pub const INVALID_CELLS_KEPT: usize = 1000;
+
+// Check name used for invalid cells in findings files
+pub const INVALID_UTF8: &str = "INVALID_UTF8";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidUtf8Cell {
+    pub line: u64,
+    pub byte_offset: u64,
+    pub column: usize,
+    pub value: String, // invalid bytes replaced by U+FFFD
+}
+
+// Cells that aren't valid UTF-8, only seen with --byte-records since the string
+// reader aborts on the first one
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct InvalidUtf8Stats {
+    pub counts: Vec<usize>,          // per column
+    pub cells: Vec<InvalidUtf8Cell>, // in file order, all of a chunk but capped at INVALID_CELLS_KEPT once merged
+}
+
+impl InvalidUtf8Stats {
+    // Count the invalid fields of a record that failed UTF-8 validation
+    fn observe(&mut self, record: &ByteRecord) {
+        let position = record.position();
+        for (column, field) in record.iter().enumerate() {
+            if std::str::from_utf8(field).is_ok() {
+                continue;
+            }
+
+            if self.counts.len() <= column {
+                self.counts.resize(column + 1, 0);
+            }
+            self.counts[column] += 1;
+            self.cells.push(InvalidUtf8Cell {
+                line: position.map_or(0, |pos| pos.line()),
+                byte_offset: position.map_or(0, |pos| pos.byte()),
+                column,
+                value: String::from_utf8_lossy(field).into_owned(),
+            });
+        }
+    }
+
+    // Chunks are merged in file order so the kept cells stay sorted
+    pub fn merge(&mut self, other: &InvalidUtf8Stats) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other_count) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other_count;
+        }
+        let room = INVALID_CELLS_KEPT.saturating_sub(self.cells.len());
+        self.cells.extend(other.cells.iter().take(room).cloned());
+    }
+
+    pub fn count_for(&self, column: usize) -> usize {
+        self.counts.get(column).copied().unwrap_or(0)
+    }
+
+    pub fn invalid_cells(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+// Turn byte records into string records for the checks. The profiles read every
+// non-missing cell as text, so validating whole records is as cheap as it gets here.
+// Valid records are validated once and reused as they are, invalid cells are decoded
+// lossily and counted
+pub fn decode_records(records: Vec<ByteRecord>) -> (Vec<StringRecord>, InvalidUtf8Stats) {
+    let mut stats = InvalidUtf8Stats::default();
+    let records = records
+        .into_iter()
+        .map(|record| match StringRecord::from_byte_record(record) {
+            Ok(record) => record,
+            Err(e) => {
+                let record = e.into_byte_record();
+                stats.observe(&record);
+                let mut lossy = StringRecord::with_capacity(record.as_slice().len(), record.len());
+                for field in &record {
+                    lossy.push_field(&String::from_utf8_lossy(field));
+                }
+                lossy.set_position(record.position().cloned());
+                lossy
+            }
+        })
+        .collect();
+    (records, stats)
+}
+
+// Like CsvChunkIterator, but reads ByteRecords so invalid UTF-8 doesn't stop the run
+pub struct ByteChunkIterator<'a, R: Read> {
+    records: csv::ByteRecordsIter<'a, R>,
+    chunk_size: usize,
+}
+
+impl<'a, R: Read> ByteChunkIterator<'a, R> {
+    pub fn new(records: csv::ByteRecordsIter<'a, R>, chunk_size: usize) -> Self {
+        ByteChunkIterator {
+            records,
+            chunk_size,
+        }
+    }
+}
+
+impl<R: Read> Iterator for ByteChunkIterator<'_, R> {
+    type Item = Result<Vec<ByteRecord>, csv::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_chunk(&mut self.records, self.chunk_size)
+    }
+}
+
+// A chunk as it comes off the reader, decoded to strings on the worker that checks it
+pub(crate) trait RawChunk: Send + 'static {
+    fn decode(self) -> (Vec<StringRecord>, InvalidUtf8Stats);
+}
+
+impl RawChunk for Vec<StringRecord> {
+    fn decode(self) -> (Vec<StringRecord>, InvalidUtf8Stats) {
+        (self, InvalidUtf8Stats::default())
+    }
+}
+
+impl RawChunk for Vec<ByteRecord> {
+    fn decode(self) -> (Vec<StringRecord>, InvalidUtf8Stats) {
+        decode_records(self)
+    }
+}
+
+// Already decoded, e.g. a byte range of a mapped file
+impl RawChunk for (Vec<StringRecord>, InvalidUtf8Stats) {
+    fn decode(self) -> (Vec<StringRecord>, InvalidUtf8Stats) {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_cells_are_counted_per_column() {
+        // 0xE9 is `é` in Latin-1, on its own it isn't valid UTF-8
+        let data = b"name,city\ncaf\xe9,Paris\nok,ok\n\xff,S\xe3o Paulo\n";
+        let mut rdr = csv::Reader::from_reader(&data[..]);
+        let records: Vec<ByteRecord> = rdr.byte_records().map(|r| r.unwrap()).collect();
+
+        let (first, first_stats) = decode_records(records[..2].to_vec());
+        let (second, second_stats) = decode_records(records[2..].to_vec());
+        assert_eq!(first[0].get(0), Some("caf\u{FFFD}"));
+        assert_eq!(first[1].get(0), Some("ok"));
+        assert_eq!(second[0].position().map(|pos| pos.line()), Some(4));
+
+        let mut stats = InvalidUtf8Stats::default();
+        stats.merge(&first_stats);
+        stats.merge(&second_stats);
+        assert_eq!(stats.counts, vec![2, 1]);
+        assert_eq!(stats.invalid_cells(), 3);
+        assert_eq!(
+            stats
+                .cells
+                .iter()
+                .map(|cell| (cell.line, cell.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 0), (4, 0), (4, 1)]
+        );
+        assert_eq!(stats.cells[2].value, "S\u{FFFD}o Paulo");
+    }
+}
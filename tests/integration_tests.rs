@@ -1,8 +1,12 @@
-use std::fs::File;
 use std::path::PathBuf;
 use true_sight_csv::{
-    prepare_csv_reader, process_csv_chunks, CsvChunkIterator, EmptyCheck, NullLikeCheck,
-    PatternCheck, ProcessingConfig, WhiteSpaceOnlyCheck,
+    decode_input, prepare_csv_reader, prepare_csv_reader_from, prepare_csv_reader_with_options,
+    process_byte_chunks, process_csv_chunks, process_csv_chunks_with_observers,
+    process_mapped_chunks, sniff_file, ByteChunkIterator, CheckRegistry, ChunkObserver,
+    Compression, CsvAggregator, CsvChunkIterator, DuplicateConfig, DuplicateDetector, EmptyCheck,
+    FindingsConfig, FindingsWriter, InferredType, InputReader, LineTerminator, MappedCsv,
+    NullLikeCheck, PatternCheck, ProcessingConfig, ReaderOptions, SparkStyleFormatter,
+    WhiteSpaceOnlyCheck,
 };
 
 // Helper function to get the path to a fixture file
@@ -52,7 +56,7 @@ fn test_csv_chunk_iterator() {
 
     let (_headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
     let chunk_size = 3;
-    let mut chunk_iterator: CsvChunkIterator<'_, File> =
+    let mut chunk_iterator: CsvChunkIterator<'_, InputReader> =
         CsvChunkIterator::new(rdr.records(), chunk_size);
 
     let first_chunk = chunk_iterator.next().unwrap().unwrap();
@@ -118,7 +122,7 @@ fn test_csv_chunk_iterator_comprehensive() {
     let (_headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
     let chunk_size = 4; // Different chunk size
 
-    let chunk_iterator: CsvChunkIterator<'_, File> =
+    let chunk_iterator: CsvChunkIterator<'_, InputReader> =
         CsvChunkIterator::new(rdr.records(), chunk_size);
 
     // Collect all chunks to verify total structure
@@ -143,7 +147,8 @@ fn test_csv_chunk_iterator_edge_cases() {
 
     // Test with chunk size larger than total records
     let (_headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
-    let mut chunk_iterator: CsvChunkIterator<'_, File> = CsvChunkIterator::new(rdr.records(), 20);
+    let mut chunk_iterator: CsvChunkIterator<'_, InputReader> =
+        CsvChunkIterator::new(rdr.records(), 20);
 
     let only_chunk = chunk_iterator.next().unwrap().unwrap();
     assert_eq!(only_chunk.len(), 12); // All records in one chunk
@@ -151,7 +156,7 @@ fn test_csv_chunk_iterator_edge_cases() {
 
     // Test with chunk size of 1
     let (_headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
-    let chunk_iterator: CsvChunkIterator<'_, File> = CsvChunkIterator::new(rdr.records(), 1);
+    let chunk_iterator: CsvChunkIterator<'_, InputReader> = CsvChunkIterator::new(rdr.records(), 1);
 
     let all_single_chunks: Result<Vec<_>, _> = chunk_iterator.collect();
     let all_single_chunks = all_single_chunks.unwrap();
@@ -176,34 +181,716 @@ fn test_process_csv_chunks() {
     let config = ProcessingConfig {
         chunk_size,
         enable_parallel: false,
+        ..Default::default()
     };
     let chunk_iterator = CsvChunkIterator::new(rdr.records(), chunk_size);
 
-    let results = process_csv_chunks(chunk_iterator, config).unwrap();
+    let mut aggregator = CsvAggregator::new(found_headers, chunk_size, &config.registry);
+    process_csv_chunks(chunk_iterator, config, &mut aggregator).unwrap();
 
-    assert_eq!(results.len(), 4); // 12 rows / 3 = 4 chunks
-
-    // Check first chunk
-    assert_eq!(results[0].chunk_number, 1);
-    assert_eq!(results[0].rows_processed, 3);
+    assert_eq!(aggregator.total_chunks(), 4); // 12 rows / 3 = 4 chunks
+    assert_eq!(aggregator.total_rows(), 12);
 
     // Check that we found some empty values (from your CSV)
-    let total_empty_found: usize = results
-        .iter()
-        .map(|r| r.empty_counts.values().sum::<usize>())
-        .sum();
+    let total_empty_found = aggregator.total_for(EmptyCheck::NAME);
     assert!(
         total_empty_found == 32,
         "Should find 32 empty values in test CSV"
     );
 
     // Check that we found some NULL-like values
-    let total_null_found: usize = results
-        .iter()
-        .map(|r| r.null_counts.values().sum::<usize>())
-        .sum();
+    let total_null_found = aggregator.total_for(NullLikeCheck::NAME);
     assert!(
         total_null_found == 15,
         "Should find 15 NULL-like values in test CSV"
     );
 }
+
+#[test]
+fn test_parallel_matches_sequential() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let run = |enable_parallel: bool| {
+        let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+        let config = ProcessingConfig {
+            chunk_size: 12,
+            enable_parallel,
+            ..Default::default()
+        };
+        let mut aggregator = CsvAggregator::new(headers, 12, &config.registry);
+        process_csv_chunks(
+            CsvChunkIterator::new(rdr.records(), 12),
+            config,
+            &mut aggregator,
+        )
+        .unwrap();
+        aggregator
+    };
+    let parallel = run(true);
+    let sequential = run(false);
+
+    // Per-thread tallies must add up to exactly what one thread counts
+    assert_eq!(parallel.total_rows(), sequential.total_rows());
+    for (p, s) in parallel
+        .column_stats()
+        .iter()
+        .zip(sequential.column_stats())
+    {
+        for check in parallel.registry().checks() {
+            assert_eq!(p.count_for(check.name()), s.count_for(check.name()));
+        }
+        assert_eq!(p.profile().types.inferred(), s.profile().types.inferred());
+        assert_eq!(p.profile().types.missing(), s.profile().types.missing());
+    }
+}
+
+// Records every chunk it is handed, to check the order chunks arrive in
+#[derive(Default)]
+struct ChunkLog {
+    chunks: Vec<Vec<csv::StringRecord>>,
+    finished: bool,
+}
+
+impl ChunkObserver for ChunkLog {
+    fn observe_chunk(
+        &mut self,
+        records: &[csv::StringRecord],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.chunks.push(records.to_vec());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pipelined_chunks_stay_in_order() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let run = |enable_parallel: bool, max_in_flight_chunks: usize| {
+        let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+        let config = ProcessingConfig {
+            chunk_size: 3,
+            enable_parallel,
+            expected_fields: Some(headers.len()),
+            max_in_flight_chunks,
+            ..Default::default()
+        };
+        let mut aggregator = CsvAggregator::new(headers, 3, &config.registry);
+        let mut log = ChunkLog::default();
+        process_csv_chunks_with_observers(
+            CsvChunkIterator::new(rdr.records(), 3),
+            config,
+            &mut aggregator,
+            &mut [&mut log],
+        )
+        .unwrap();
+        assert!(log.finished);
+        (aggregator, log.chunks)
+    };
+    let (sequential, expected) = run(false, 1);
+    assert!(expected.len() > 3);
+
+    for max_in_flight_chunks in [1, 2, 5] {
+        let (pipelined, chunks) = run(true, max_in_flight_chunks);
+        // Observers see the chunks in file order whatever order the workers finish in
+        assert_eq!(chunks, expected);
+        assert_eq!(pipelined.total_chunks(), sequential.total_chunks());
+        assert_eq!(pipelined.total_rows(), sequential.total_rows());
+        assert_eq!(pipelined.row_shape(), sequential.row_shape());
+        for check in sequential.registry().checks() {
+            assert_eq!(
+                pipelined.total_for(check.name()),
+                sequential.total_for(check.name())
+            );
+        }
+    }
+}
+
+#[test]
+fn test_findings_recorded_by_workers_stay_in_order() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let run = |enable_parallel: bool| {
+        let path = std::env::temp_dir().join(format!(
+            "true-sight-findings-order-{}-{}.csv",
+            std::process::id(),
+            enable_parallel
+        ));
+        let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+        let registry = CheckRegistry::new();
+        let mut writer =
+            FindingsWriter::create(&path, FindingsConfig::default(), &registry).unwrap();
+        writer.start_source("sample-warehouse-data.csv", &headers);
+        let mut aggregator = CsvAggregator::new(headers, 3, &registry);
+        process_csv_chunks_with_observers(
+            CsvChunkIterator::new(rdr.records(), 3),
+            ProcessingConfig {
+                chunk_size: 3,
+                enable_parallel,
+                ..Default::default()
+            },
+            &mut aggregator,
+            &mut [&mut writer],
+        )
+        .unwrap();
+
+        // Every counted cell is written once
+        let counted: usize = registry
+            .checks()
+            .iter()
+            .map(|check| aggregator.total_for(check.name()))
+            .sum();
+        assert_eq!(writer.written(), counted);
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        output
+    };
+
+    assert_eq!(run(true), run(false));
+}
+
+#[test]
+fn test_byte_records_report_invalid_utf8() {
+    // Latin-1 `é` and `ã` in a file that is otherwise UTF-8
+    let data = b"id,name,city\n1,caf\xe9,Paris\n2,,Lyon\n3,ok,S\xe3o Paulo\n4,NULL,Nice\n";
+    let path = std::env::temp_dir().join(format!("true-sight-latin1-{}.csv", std::process::id()));
+    std::fs::write(&path, data).unwrap();
+
+    // The string reader stops at the first invalid cell
+    let (headers, mut rdr) = prepare_csv_reader(&path).unwrap();
+    let registry = CheckRegistry::new();
+    let mut aggregator = CsvAggregator::new(headers.clone(), 2, &registry);
+    let error = process_csv_chunks(
+        CsvChunkIterator::new(rdr.records(), 2),
+        ProcessingConfig::default(),
+        &mut aggregator,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<csv::Error>().map(|e| e.kind()),
+        Some(csv::ErrorKind::Utf8 { .. })
+    ));
+
+    let options = ReaderOptions {
+        byte_records: true,
+        ..Default::default()
+    };
+    for enable_parallel in [true, false] {
+        let (_headers, mut rdr) = prepare_csv_reader_with_options(&path, &options).unwrap();
+        let mut aggregator = CsvAggregator::new(headers.clone(), 2, &registry);
+        process_byte_chunks(
+            ByteChunkIterator::new(rdr.byte_records(), 2),
+            ProcessingConfig {
+                chunk_size: 2,
+                enable_parallel,
+                ..Default::default()
+            },
+            &mut aggregator,
+        )
+        .unwrap();
+
+        // Every row is still checked, the invalid cells are listed with their lines
+        assert_eq!(aggregator.total_rows(), 4);
+        assert_eq!(aggregator.total_for(EmptyCheck::NAME), 1);
+        assert_eq!(aggregator.total_for(NullLikeCheck::NAME), 1);
+        let invalid = aggregator.invalid_utf8();
+        assert_eq!(invalid.invalid_cells(), 2);
+        assert_eq!(invalid.count_for(1), 1);
+        assert_eq!(invalid.count_for(2), 1);
+        assert_eq!(
+            invalid
+                .cells
+                .iter()
+                .map(|cell| (cell.line, cell.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 1), (4, 2)]
+        );
+    }
+
+    // Memory-mapped byte ranges report the same cells
+    let mapped = MappedCsv::open(&path, &options).unwrap();
+    let mut aggregator = CsvAggregator::new(headers, 1, &registry);
+    let config = ProcessingConfig {
+        chunk_size: 1,
+        ..Default::default()
+    };
+    process_mapped_chunks(&mapped, config, &mut aggregator, &mut []).unwrap();
+    assert_eq!(aggregator.invalid_utf8().invalid_cells(), 2);
+    assert_eq!(aggregator.invalid_utf8().cells[1].value, "S\u{FFFD}o Paulo");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_byte_records_long_invalid_value_in_report() {
+    // 30 Latin-1 bytes next to valid UTF-8, each one decodes to a 3-byte U+FFFD that
+    // has to be cut on a character boundary in the 25 character report column
+    let mut data = b"name,city\n".to_vec();
+    data.extend(std::iter::repeat_n(0xe9, 30));
+    data.extend_from_slice(b",S\xc3\xa3o Paulo\n");
+    let path = std::env::temp_dir().join(format!("true-sight-long-{}.csv", std::process::id()));
+    std::fs::write(&path, data).unwrap();
+
+    let options = ReaderOptions {
+        byte_records: true,
+        ..Default::default()
+    };
+    let (headers, mut rdr) = prepare_csv_reader_with_options(&path, &options).unwrap();
+    let registry = CheckRegistry::new();
+    let mut aggregator = CsvAggregator::new(headers, 10, &registry);
+    process_byte_chunks(
+        ByteChunkIterator::new(rdr.byte_records(), 10),
+        ProcessingConfig::default(),
+        &mut aggregator,
+    )
+    .unwrap();
+
+    let report = SparkStyleFormatter::new()
+        .with_max_width(25)
+        .format_report(&aggregator);
+    assert!(report.contains(&format!("| {}... |", "\u{FFFD}".repeat(22))));
+    assert!(report.contains("Invalid UTF-8 cells: 1 (name: 1)"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_byte_records_cli_reads_stray_latin1_as_utf8() {
+    // Detection would read this file as Windows-1252, where `\xe9` is a valid `é`
+    let path = std::env::temp_dir().join(format!("true-sight-stray-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        b"\xef\xbb\xbfname,city\ncaf\xe9,Paris\nok,S\xc3\xa3o Paulo\n",
+    )
+    .unwrap();
+    let run = |extra: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_true-sight-csv"))
+            .arg(&path)
+            .arg("--byte-records")
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Read as UTF-8 with the BOM stripped, so the stray byte is reported
+    let stdout = run(&[]);
+    assert!(stdout.contains("Invalid UTF-8 cells: 1 (name: 1)"));
+    assert!(!stdout.contains("\u{FEFF}"));
+
+    // An explicit --encoding is still honoured
+    let stdout = run(&["--encoding", "latin1"]);
+    assert!(!stdout.contains("Invalid UTF-8 cells"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_legacy_encodings_read_like_utf8() {
+    let text = "id,name,city\n1,café,Paris\n2,,Lyon\n3,NULL,São Paulo\n";
+    let run = |bytes: Vec<u8>| {
+        let (encoding, input) = decode_input(Box::new(std::io::Cursor::new(bytes)), None).unwrap();
+        let (headers, mut rdr) = prepare_csv_reader_from(input, &ReaderOptions::default()).unwrap();
+        let registry = CheckRegistry::new();
+        let mut aggregator = CsvAggregator::new(headers, 2, &registry);
+        process_csv_chunks(
+            CsvChunkIterator::new(rdr.records(), 2),
+            ProcessingConfig::default(),
+            &mut aggregator,
+        )
+        .unwrap();
+        (encoding, aggregator)
+    };
+
+    let (encoding, expected) = run(text.as_bytes().to_vec());
+    assert_eq!((encoding.name(), encoding.bom), ("UTF-8", false));
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    utf16.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    let latin1: Vec<u8> = text.chars().map(|c| c as u32 as u8).collect();
+    let bom_utf8 = [b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat();
+
+    for (bytes, name, bom) in [
+        (utf16, "UTF-16LE", true),
+        (latin1, "windows-1252", false),
+        (bom_utf8, "UTF-8", true),
+    ] {
+        let (encoding, aggregator) = run(bytes);
+        assert_eq!((encoding.name(), encoding.bom), (name, bom));
+        // The BOM doesn't end up in the first header name
+        assert_eq!(aggregator.headers(), expected.headers());
+        assert_eq!(aggregator.total_rows(), 3);
+        for check in expected.registry().checks() {
+            assert_eq!(
+                aggregator.total_for(check.name()),
+                expected.total_for(check.name())
+            );
+        }
+    }
+
+    // Read without transcoding, a UTF-8 BOM is still stripped from the header
+    let bom_utf8 = [b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat();
+    let (headers, _) = prepare_csv_reader_from(
+        Box::new(std::io::Cursor::new(bom_utf8)),
+        &ReaderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(headers[0], "id");
+}
+
+#[test]
+fn test_mapped_chunks_match_streamed_chunks() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let config = || ProcessingConfig {
+        chunk_size: 5,
+        expected_fields: Some(9),
+        ..Default::default()
+    };
+
+    let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+    let registry = CheckRegistry::new();
+    let mut streamed = CsvAggregator::new(headers.clone(), 5, &registry);
+    process_csv_chunks(
+        CsvChunkIterator::new(rdr.records(), 5),
+        config(),
+        &mut streamed,
+    )
+    .unwrap();
+
+    let mapped = MappedCsv::open(&test_path, &ReaderOptions::default()).unwrap();
+    assert_eq!(mapped.headers(), headers.as_slice());
+    let mut aggregator = CsvAggregator::new(headers, 5, &registry);
+    process_mapped_chunks(&mapped, config(), &mut aggregator, &mut []).unwrap();
+
+    // Byte ranges hold the same rows as the streamed chunks
+    assert_eq!(aggregator.total_chunks(), streamed.total_chunks());
+    assert_eq!(aggregator.total_rows(), streamed.total_rows());
+    for check in registry.checks() {
+        for column in 0..9 {
+            assert_eq!(
+                aggregator.count_for(check.name(), column),
+                streamed.count_for(check.name(), column)
+            );
+        }
+    }
+}
+
+// A custom check registered from outside the crate
+struct StartsWithNullCheck;
+
+impl PatternCheck for StartsWithNullCheck {
+    fn name(&self) -> &str {
+        "StartsWithNull"
+    }
+    fn check(&self, value: &str) -> bool {
+        value.trim_start().starts_with("NULL")
+    }
+    fn show_check_pattern(&self) -> &str {
+        "NULL..."
+    }
+}
+
+#[test]
+fn test_custom_check_registry() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+
+    let mut registry = CheckRegistry::empty();
+    registry.register(StartsWithNullCheck).unwrap();
+    registry.register(EmptyCheck::new()).unwrap();
+
+    // Names must be unique since results are keyed by name
+    assert!(registry.register(EmptyCheck::new()).is_err());
+    assert_eq!(registry.len(), 2);
+
+    let config = ProcessingConfig {
+        chunk_size: 5,
+        enable_parallel: true,
+        registry,
+        ..Default::default()
+    };
+    let chunk_iterator = CsvChunkIterator::new(rdr.records(), 5);
+    let mut aggregator = CsvAggregator::new(headers, 5, &config.registry);
+    process_csv_chunks(chunk_iterator, config, &mut aggregator).unwrap();
+
+    assert_eq!(aggregator.total_for("StartsWithNull"), 11);
+    assert_eq!(aggregator.total_for(EmptyCheck::NAME), 32);
+
+    // Checks that were not registered produce no results
+    assert_eq!(aggregator.total_for(NullLikeCheck::NAME), 0);
+}
+
+// A check that blows up on one value, like a buggy user check
+struct PanickingCheck;
+
+impl PatternCheck for PanickingCheck {
+    fn name(&self) -> &str {
+        "Panicking"
+    }
+    fn check(&self, value: &str) -> bool {
+        if value == "NULL" {
+            panic!("cannot handle {}", value);
+        }
+        false
+    }
+    fn show_check_pattern(&self) -> &str {
+        "-"
+    }
+}
+
+#[test]
+fn test_panicking_check_fails_the_pipelined_run() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+
+    let mut registry = CheckRegistry::empty();
+    registry.register(PanickingCheck).unwrap();
+    let config = ProcessingConfig {
+        chunk_size: 5,
+        enable_parallel: true,
+        registry,
+        ..Default::default()
+    };
+    let mut aggregator = CsvAggregator::new(headers, 5, &config.registry);
+
+    // The panic comes back as an error instead of a chunk that never arrives
+    let error = process_csv_chunks(
+        CsvChunkIterator::new(rdr.records(), 5),
+        config,
+        &mut aggregator,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("panicked: cannot handle NULL"));
+}
+
+#[test]
+fn test_type_inference_across_chunks() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+
+    let config = ProcessingConfig {
+        chunk_size: 2,
+        enable_parallel: true,
+        ..Default::default()
+    };
+    let chunk_iterator = CsvChunkIterator::new(rdr.records(), 2);
+    let mut aggregator = CsvAggregator::new(headers, 2, &config.registry);
+    process_csv_chunks(chunk_iterator, config, &mut aggregator).unwrap();
+    let profiles: Vec<_> = aggregator
+        .column_stats()
+        .iter()
+        .map(|stats| stats.profile())
+        .collect();
+
+    assert_eq!(profiles.len(), 9);
+
+    // quantity: 6 integers, "hello trim" doesn't conform, NULL/nOne/blanks are missing
+    let quantity = &profiles[3].types;
+    assert_eq!(quantity.inferred(), Some(InferredType::Integer));
+    assert_eq!(quantity.mismatch_count(), 1);
+    assert_eq!(quantity.missing(), 5);
+
+    assert_eq!(profiles[1].types.inferred(), Some(InferredType::Date));
+    assert_eq!(profiles[4].types.inferred(), Some(InferredType::Decimal));
+    assert_eq!(profiles[7].types.inferred(), Some(InferredType::DateTime));
+    assert_eq!(profiles[6].types.inferred(), Some(InferredType::String));
+}
+
+#[test]
+fn test_duplicate_keys_across_chunks() {
+    let test_path = get_fixture_path("sample-warehouse-data.csv");
+    let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+
+    let key =
+        DuplicateDetector::resolve_key_columns(&["customer_id".to_string()], &headers).unwrap();
+    let mut by_key = DuplicateDetector::new(
+        DuplicateConfig {
+            key_columns: Some(key),
+            ..Default::default()
+        },
+        &headers,
+    )
+    .unwrap();
+    let mut full_row = DuplicateDetector::new(DuplicateConfig::default(), &headers).unwrap();
+
+    let config = ProcessingConfig {
+        chunk_size: 3,
+        ..Default::default()
+    };
+    let chunk_iterator = CsvChunkIterator::new(rdr.records(), 3);
+    let mut aggregator = CsvAggregator::new(headers, 3, &config.registry);
+    process_csv_chunks_with_observers(
+        chunk_iterator,
+        config,
+        &mut aggregator,
+        &mut [&mut by_key as &mut dyn ChunkObserver, &mut full_row],
+    )
+    .unwrap();
+
+    // 1005 twice and "NULL" four times, line numbers count the header as line 1
+    let report = by_key.report().unwrap();
+    assert_eq!(report.duplicate_groups, 2);
+    assert_eq!(report.duplicate_rows, 4);
+    assert_eq!(report.groups[0].line_numbers, vec![6, 7]);
+    assert_eq!(report.groups[1].line_numbers, vec![9, 10, 11, 12]);
+
+    assert_eq!(full_row.report().unwrap().duplicate_groups, 0);
+}
+
+#[test]
+fn test_unique_rule_counts_once_next_to_key_report() {
+    let config =
+        std::env::temp_dir().join(format!("true-sight-unique-{}.toml", std::process::id()));
+    std::fs::write(&config, "[columns.customer_id]\nunique = true\n").unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_true-sight-csv"))
+        .arg(get_fixture_path("sample-warehouse-data.csv"))
+        .arg("--config")
+        .arg(&config)
+        .args(["--duplicates", "--key", "customer_id", "--format", "json"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&config).unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    // The --key report is listed once, the rule counts its own detector only
+    let key_reports: Vec<_> = report["duplicates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|report| report["key_columns"] == serde_json::json!(["customer_id"]))
+        .collect();
+    assert_eq!(key_reports.len(), 1);
+    assert_eq!(key_reports[0]["duplicate_rows"], 4);
+    assert_eq!(report["rules"][0]["rule"], "unique");
+    assert_eq!(report["rules"][0]["violations"], 4);
+}
+
+#[test]
+fn test_tolerant_mode_reports_ragged_rows() {
+    let test_path = get_fixture_path("sample-ragged-data.csv");
+
+    // The default reader stops at the first short row
+    let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+    let chunk_iterator = CsvChunkIterator::new(rdr.records(), 2);
+    let mut aggregator = CsvAggregator::new(headers, 2, &CheckRegistry::new());
+    assert!(
+        process_csv_chunks(chunk_iterator, ProcessingConfig::default(), &mut aggregator).is_err()
+    );
+
+    let options = ReaderOptions {
+        flexible: true,
+        ..Default::default()
+    };
+    let (headers, mut rdr) = prepare_csv_reader_with_options(&test_path, &options).unwrap();
+    let config = ProcessingConfig {
+        chunk_size: 2,
+        expected_fields: Some(headers.len()),
+        ..Default::default()
+    };
+    let chunk_iterator = CsvChunkIterator::new(rdr.records(), 2);
+    let mut aggregator = CsvAggregator::new(headers, 2, &CheckRegistry::new());
+    process_csv_chunks(chunk_iterator, config, &mut aggregator).unwrap();
+
+    let row_shape = aggregator.row_shape();
+    assert_eq!(aggregator.total_rows(), 5);
+    assert_eq!(row_shape.short_rows, 2);
+    assert_eq!(row_shape.long_rows, 1);
+    let lines: Vec<(u64, usize)> = row_shape
+        .rows
+        .iter()
+        .map(|row| (row.line, row.actual_fields))
+        .collect();
+    assert_eq!(lines, vec![(3, 2), (4, 4), (6, 1)]);
+}
+
+#[test]
+fn test_sniffed_dialect_is_used_for_parsing() {
+    let test_path = get_fixture_path("sample-semicolon-data.csv");
+
+    let dialect = sniff_file(&test_path).unwrap();
+    assert_eq!(dialect.delimiter, b';');
+    assert_eq!(dialect.line_terminator, LineTerminator::CrLf);
+    assert!(dialect.has_headers);
+
+    let options = ReaderOptions {
+        dialect,
+        ..Default::default()
+    };
+    let (headers, mut rdr) = prepare_csv_reader_with_options(&test_path, &options).unwrap();
+    assert_eq!(
+        headers,
+        vec!["customer_id", "product_sku", "unit_price", "note"]
+    );
+
+    let records: Vec<_> = rdr.records().map(|r| r.unwrap()).collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(&records[0][3], "first; order");
+}
+
+#[test]
+fn test_gzip_input_matches_plain_csv() {
+    let totals = |fixture: &str| {
+        let (headers, mut rdr) = prepare_csv_reader(&get_fixture_path(fixture)).unwrap();
+        let chunk_iterator = CsvChunkIterator::new(rdr.records(), 5);
+        let config = ProcessingConfig {
+            chunk_size: 5,
+            ..Default::default()
+        };
+        let mut aggregator = CsvAggregator::new(headers, 5, &config.registry);
+        process_csv_chunks(chunk_iterator, config, &mut aggregator).unwrap();
+        (
+            aggregator.total_rows(),
+            aggregator.total_for(NullLikeCheck::NAME),
+        )
+    };
+
+    let gzip_path = get_fixture_path("sample-warehouse-data.csv.gz");
+    assert_eq!(Compression::detect(&gzip_path).unwrap(), Compression::Gzip);
+    assert_eq!(
+        totals("sample-warehouse-data.csv.gz"),
+        totals("sample-warehouse-data.csv")
+    );
+}
+
+#[test]
+fn test_merge_partial_runs() {
+    let run = || {
+        let test_path = get_fixture_path("sample-warehouse-data.csv");
+        let (headers, mut rdr) = prepare_csv_reader(&test_path).unwrap();
+        let config = ProcessingConfig {
+            chunk_size: 5,
+            ..Default::default()
+        };
+        let mut aggregator = CsvAggregator::new(headers, 5, &config.registry);
+        process_csv_chunks(
+            CsvChunkIterator::new(rdr.records(), 5),
+            config,
+            &mut aggregator,
+        )
+        .unwrap();
+        aggregator
+    };
+
+    // Two partial runs combine into the totals of both
+    let single = run();
+    let mut combined = run();
+    combined.merge(run());
+    assert_eq!(combined.total_rows(), 2 * single.total_rows());
+    assert_eq!(combined.total_chunks(), 2 * single.total_chunks());
+    assert_eq!(
+        combined.total_for(EmptyCheck::NAME),
+        2 * single.total_for(EmptyCheck::NAME)
+    );
+    assert_eq!(
+        combined.count_for(NullLikeCheck::NAME, 3),
+        2 * single.count_for(NullLikeCheck::NAME, 3)
+    );
+    let quantity = combined.column_stats()[3].profile();
+    assert_eq!(quantity.types.inferred(), Some(InferredType::Integer));
+    assert_eq!(
+        quantity.types.missing(),
+        2 * single.column_stats()[3].profile().types.missing()
+    );
+}